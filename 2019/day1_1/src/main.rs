@@ -1,4 +1,3 @@
-use std::error::Error;
 use std::fs::File;
 use std::io;
 use std::io::{BufRead, BufReader};
@@ -8,7 +7,7 @@ fn main() -> Result<(), io::Error> {
     let path = Path::new("input1.txt");
     let file = match File::open(path) {
         Ok(file) => file,
-        Err(why) => panic!("Couldn't read the input\n{}", why.description()),
+        Err(why) => panic!("Couldn't read the input\n{}", why),
     };
 
     let total_fuel: usize = BufReader::new(file)
@@ -1,4 +1,3 @@
-use std::error::Error;
 use std::fs::File;
 use std::io;
 use std::io::Read;
@@ -8,7 +7,7 @@ fn main() -> Result<(), io::Error> {
     let path = Path::new("input1.txt");
     let mut file = match File::open(path) {
         Ok(file) => file,
-        Err(why) => panic!("Couldn't read the input: {}", why.description()),
+        Err(why) => panic!("Couldn't read the input: {}", why),
     };
     let mut string_buffer = String::new();
     file.read_to_string(&mut string_buffer)
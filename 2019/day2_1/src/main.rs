@@ -1,17 +1,17 @@
-use std::io;
+use std::error::Error;
 use std::path::Path;
 
 use intcode_computer::Machine;
 
-fn main() -> Result<(), io::Error> {
+fn main() -> Result<(), Box<dyn Error>> {
     let path = Path::new("input2.txt");
 
     let mut machine = Machine::from_path(path)?;
-    machine.set_noun_verb(12, 2);
+    machine.set_noun_verb(12, 2)?;
 
-    let mut input_fn = || 0isize;
+    let mut input_fn = || None;
     let mut output_fn = |_out| {};
-    machine.execute(&mut input_fn, &mut output_fn);
+    machine.execute(&mut input_fn, &mut output_fn)?;
 
     println!("Position 0 contains: {}", machine.result());
 
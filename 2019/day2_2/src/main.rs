@@ -2,6 +2,7 @@ use std::env;
 use std::error::Error;
 use std::path::Path;
 
+use intcode_computer::symbolic::solve_noun_verb;
 use intcode_computer::Machine;
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -10,24 +11,15 @@ fn main() -> Result<(), Box<dyn Error>> {
         .parse::<isize>()?;
 
     let path = Path::new("input2.txt");
-    let initial_machine = Machine::from_path(path)?;
+    let machine = Machine::from_path(path)?;
 
-    'outer: for verb in 0isize..=99isize {
-        for noun in 0isize..=99isize {
-            let mut machine = initial_machine.clone();
-            machine.set_noun_verb(noun, verb);
-
-            let mut input_fn = || 0isize;
-            let mut output_fn = |_out| {};
-            machine.execute(&mut input_fn, &mut output_fn);
-
-            if machine.result() == target_result {
-                println!("Got result: {}", machine.result());
-                println!("Noun: {}, Verb: {}", noun, verb);
-                println!("Code: {}", 100 * noun + verb);
-                break 'outer;
-            }
+    match solve_noun_verb(&machine, target_result, 0..=99) {
+        Some((noun, verb)) => {
+            println!("Got result: {}", target_result);
+            println!("Noun: {}, Verb: {}", noun, verb);
+            println!("Code: {}", 100 * noun + verb);
         }
+        None => println!("No noun and verb give {}", target_result),
     }
 
     Ok(())
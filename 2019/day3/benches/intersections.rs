@@ -0,0 +1,64 @@
+//! Compares the time taken to find where two long generated wires cross by checking every pair
+//! of segments and with a sweep line. Run with `cargo bench -p day3`.
+use std::time::{Duration, Instant};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::Lcg;
+
+use day3::sweep;
+use day3::wire::{self, Overlap, Wire};
+
+// The number of segments in each wire; the puzzle inputs have about 300
+const SIZES: [usize; 4] = [300, 1000, 3000, 10_000];
+const RUNS: usize = 5;
+
+// Generates a wire that turns at every step, like those in the puzzle input
+fn random_wire(rng: &mut Lcg, segments: usize) -> Wire {
+    let mut horizontal = rng.next(2) == 0;
+    let path = (0..segments)
+        .map(|_| {
+            let directions = if horizontal { ['L', 'R'] } else { ['U', 'D'] };
+            horizontal = !horizontal;
+            format!("{}{}", directions[rng.next(2)], 1 + rng.next(1000))
+        })
+        .collect::<Vec<_>>();
+    path.join(",").parse().unwrap()
+}
+
+// Gets the quickest of several runs, along with what was found
+fn time(find: fn(&[Wire]) -> Vec<Overlap>, wires: &[Wire]) -> (Duration, Vec<Overlap>) {
+    let mut best = Duration::MAX;
+    let mut found = Vec::new();
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        found = find(wires);
+        best = best.min(start.elapsed());
+    }
+    (best, found)
+}
+
+fn main() {
+    let mut rng = Lcg(2019);
+
+    println!(
+        "{:>9} {:>13} {:>12} {:>12} {:>8}",
+        "segments", "overlaps", "every pair", "sweep", "speedup"
+    );
+    for &size in SIZES.iter() {
+        let wires = [random_wire(&mut rng, size), random_wire(&mut rng, size)];
+        let (naive, expected) = time(wire::overlaps, &wires);
+        let (swept, found) = time(sweep::overlaps, &wires);
+        assert_eq!(expected, found);
+
+        println!(
+            "{:>9} {:>13} {:>12.2?} {:>12.2?} {:>7.1}x",
+            size,
+            found.len(),
+            naive,
+            swept,
+            naive.as_secs_f64() / swept.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,5 @@
+//! # Crossed Wires AOC2019
+//! Wires laid out on a grid, as described in problem 3, and the places where they cross.
+//!
+pub mod sweep;
+pub mod wire;
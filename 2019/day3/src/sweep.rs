@@ -0,0 +1,169 @@
+//! # Sweep
+//! Finding where wires meet with a sweep line, for inputs too large to compare every segment of
+//! each wire with every segment of the others.
+//!
+//! A vertical line sweeps across the grid from left to right. Horizontal segments are entered
+//! into a set ordered by y when the line reaches their left end and removed once it passes
+//! their right end, and each vertical segment the line reaches looks up the horizontal segments
+//! within its span of y. Segments that lie along the same line are found separately, by sorting
+//! those on each line by where they start, and comparing each only with the segments of other
+//! wires that have not yet ended. For n segments of which m pairs meet, counting pairs from the
+//! same wire that cross, `overlaps` takes O((n + m) log n) time, rather than the O(n²) of
+//! `wire::overlaps`.
+//!
+//! Segments that run alongside each other share every point of the stretch they overlap, so
+//! `intersections`, which lists each point, also takes time and memory for every one of the k
+//! points shared. That can be far more than m; two wires of `R4000000000` share four billion
+//! points. Where wires may overlap for long distances, use `overlaps` and take the closest
+//! distance and fewest steps from each `Overlap` instead.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::wire::{self, Intersection, Overlap, Segment, Wire};
+
+// A segment along with the index of its wire
+type Part<'a> = (usize, &'a Segment);
+
+// What happens as the sweep line reaches a segment. At the same x, horizontal segments are
+// entered before vertical ones cross the line and removed after, so that ends which touch meet.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Event {
+    Enter,
+    Cross,
+    Leave,
+}
+
+/// Find every point, other than the origin, where two of `wires` meet, giving the same
+/// intersections as `wire::intersections`
+/// ### Examples
+/// ```
+/// use day3::sweep;
+/// use day3::wire::{self, parse_wires};
+///
+/// // The third wire runs alongside the first from (2, 0) to (3, 0), and touches the second
+/// // at (0, 1)
+/// let wires = parse_wires("R8,U5,L5,D3\nU7,R6,D4,L4\nU1,R2,D1,R1").unwrap();
+/// let found = sweep::intersections(&wires);
+/// assert_eq!(wire::intersections(&wires), found);
+/// assert_eq!(5, found.len());
+/// assert_eq!(Some(1), found.iter().map(|found| found.distance()).min());
+/// ```
+pub fn intersections(wires: &[Wire]) -> Vec<Intersection> {
+    wire::points(&overlaps(wires))
+}
+
+/// Find where segments of different wires meet, giving the same overlaps as `wire::overlaps`
+/// ### Examples
+/// ```
+/// use day3::sweep;
+/// use day3::wire::{self, parse_wires, Overlap};
+///
+/// // The third wire runs alongside the first from the origin to (8, 0), where the first turns
+/// // up and crosses it again, then far beyond it
+/// let wires = parse_wires("R8,U5,L5,D3\nU7,R6,D4,L4\nR4000000000").unwrap();
+/// let found = sweep::overlaps(&wires);
+/// assert_eq!(wire::overlaps(&wires), found);
+/// assert_eq!(4, found.len());
+/// assert_eq!(Some(1), found.iter().filter_map(Overlap::distance).min());
+/// assert_eq!(Some(1 + 1), found.iter().filter_map(Overlap::steps).min());
+/// ```
+pub fn overlaps(wires: &[Wire]) -> Vec<Overlap> {
+    let parts = wires
+        .iter()
+        .enumerate()
+        .flat_map(|(index, wire)| wire.segments().iter().map(move |segment| (index, segment)))
+        .collect::<Vec<Part>>();
+
+    let mut found = Vec::new();
+    perpendicular(&parts, &mut found);
+    collinear(&parts, true, &mut found);
+    collinear(&parts, false, &mut found);
+    found.sort_unstable();
+    found
+}
+
+// Finds where horizontal segments meet vertical ones
+fn perpendicular(parts: &[Part], found: &mut Vec<Overlap>) {
+    let mut events = Vec::new();
+    for (id, &(_, segment)) in parts.iter().enumerate() {
+        let (low, high) = segment.bounds();
+        if segment.direction.is_horizontal() {
+            events.push((low.x, Event::Enter, id));
+            events.push((high.x, Event::Leave, id));
+        } else {
+            events.push((low.x, Event::Cross, id));
+        }
+    }
+    events.sort_unstable();
+
+    // The horizontal segments the sweep line is passing through, by y then id
+    let mut active = BTreeMap::new();
+    for (_, event, id) in events {
+        let (low, high) = parts[id].1.bounds();
+        match event {
+            Event::Enter => {
+                active.insert((low.y, id), parts[id]);
+            }
+            Event::Leave => {
+                active.remove(&(low.y, id));
+            }
+            Event::Cross => {
+                for (_, &other) in active.range((low.y, 0)..=(high.y, usize::MAX)) {
+                    found.extend(Overlap::between(other, parts[id]));
+                }
+            }
+        }
+    }
+}
+
+// Finds where segments lying along the same line overlap
+fn collinear(parts: &[Part], horizontal: bool, found: &mut Vec<Overlap>) {
+    // Each segment as the line it lies along and its span on that line
+    let mut lines = parts
+        .iter()
+        .filter(|(_, segment)| segment.direction.is_horizontal() == horizontal)
+        .map(|&part| {
+            let (low, high) = part.1.bounds();
+            if horizontal {
+                (low.y, low.x, high.x, part)
+            } else {
+                (low.x, low.y, high.y, part)
+            }
+        })
+        .collect::<Vec<_>>();
+    lines.sort_unstable_by_key(|&(line, start, _, _)| (line, start));
+
+    // The segments on the current line that have not ended before the current one starts, by
+    // where they end and by wire. Only wires with such a segment are kept, so each segment only
+    // visits segments of other wires that it overlaps.
+    let mut ends: BTreeSet<(i64, usize)> = BTreeSet::new();
+    let mut active: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+    let mut current = None;
+    for (id, &(line, start, end, part)) in lines.iter().enumerate() {
+        if current != Some(line) {
+            current = Some(line);
+            ends.clear();
+            active.clear();
+        }
+        while ends
+            .first()
+            .is_some_and(|&(other_end, _)| other_end < start)
+        {
+            let (_, other) = ends.pop_first().unwrap();
+            let wire = lines[other].3 .0;
+            if let Some(segments) = active.get_mut(&wire) {
+                segments.remove(&other);
+                if segments.is_empty() {
+                    active.remove(&wire);
+                }
+            }
+        }
+
+        for (_, segments) in active.iter().filter(|(&wire, _)| wire != part.0) {
+            for &other in segments {
+                found.extend(Overlap::between(lines[other].3, part));
+            }
+        }
+        ends.insert((end, id));
+        active.entry(part.0).or_default().insert(id);
+    }
+}
@@ -0,0 +1,484 @@
+//! # Wire
+//! Wires that start at a shared origin and follow a path of horizontal and vertical steps.
+//!
+//! A wire is written as a comma separated list of steps, each a direction (`U`, `D`, `L` or `R`)
+//! followed by a distance, such as `R8,U5,L5,D3`. Distances along a wire are counted in steps
+//! from the origin; where a wire passes through a point more than once, the first visit counts.
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// ## WireError
+/// The reasons a wire can fail to parse
+#[derive(PartialEq, Debug, Clone)]
+pub enum WireError {
+    /// The wire on the given line, starting from 1, has no steps
+    Empty { line: usize },
+    /// A step of the wire on the given line is not a direction followed by a distance. Steps
+    /// are counted from 1.
+    BadStep {
+        line: usize,
+        step: usize,
+        found: String,
+    },
+}
+
+impl Display for WireError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            WireError::Empty { line } => write!(f, "line {}: the wire has no steps", line),
+            WireError::BadStep { line, step, found } => write!(
+                f,
+                "line {}, step {}: expected a direction and distance, found {:?}",
+                line, step, found
+            ),
+        }
+    }
+}
+
+impl Error for WireError {}
+
+/// ## Direction
+/// The ways a wire can go from a point
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// Get the `Direction` for its letter
+    pub fn from_char(letter: char) -> Option<Direction> {
+        match letter {
+            'U' => Some(Direction::Up),
+            'D' => Some(Direction::Down),
+            'L' => Some(Direction::Left),
+            'R' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    /// Get the letter for the direction
+    pub fn letter(self) -> char {
+        match self {
+            Direction::Up => 'U',
+            Direction::Down => 'D',
+            Direction::Left => 'L',
+            Direction::Right => 'R',
+        }
+    }
+
+    /// Get the change in x and y of a single step in the direction
+    pub fn delta(self) -> (i64, i64) {
+        match self {
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+
+    /// Get whether the direction is left or right
+    pub fn is_horizontal(self) -> bool {
+        self == Direction::Left || self == Direction::Right
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.letter())
+    }
+}
+
+/// ## Point
+/// A position on the grid, with the origin at (0, 0)
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    /// Create a new `Point`
+    pub fn new(x: i64, y: i64) -> Point {
+        Point { x, y }
+    }
+
+    /// Get the Manhattan distance of the point from the origin
+    pub fn distance(self) -> i64 {
+        self.x.abs() + self.y.abs()
+    }
+
+    /// Get the Manhattan distance between two points
+    pub fn distance_to(self, other: Point) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// ## Segment
+/// A straight part of a wire
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub struct Segment {
+    /// Where the segment starts
+    pub start: Point,
+    pub direction: Direction,
+    pub length: i64,
+    /// The number of steps along the wire to the start of the segment
+    pub steps: i64,
+}
+
+impl Segment {
+    /// Get where the segment ends
+    pub fn end(&self) -> Point {
+        let (dx, dy) = self.direction.delta();
+        Point::new(
+            self.start.x + dx * self.length,
+            self.start.y + dy * self.length,
+        )
+    }
+
+    /// Get the corners of the box covered by the segment; the lowest x and y, then the highest
+    pub fn bounds(&self) -> (Point, Point) {
+        let (start, end) = (self.start, self.end());
+        (
+            Point::new(start.x.min(end.x), start.y.min(end.y)),
+            Point::new(start.x.max(end.x), start.y.max(end.y)),
+        )
+    }
+
+    /// Get whether `point` lies on the segment
+    pub fn contains(&self, point: Point) -> bool {
+        let (low, high) = self.bounds();
+        (low.x..=high.x).contains(&point.x) && (low.y..=high.y).contains(&point.y)
+    }
+
+    /// Get the number of steps along the wire to `point`, if it lies on the segment
+    pub fn steps_to(&self, point: Point) -> Option<i64> {
+        if self.contains(point) {
+            Some(self.steps + self.start.distance_to(point))
+        } else {
+            None
+        }
+    }
+
+    /// Get the points the segment shares with `other`, as the corners of the box they cover.
+    /// Perpendicular segments share at most one point, while segments on the same line may
+    /// overlap for some distance.
+    /// ### Examples
+    /// ```
+    /// use day3::wire::{Point, Wire};
+    ///
+    /// let first = "R8".parse::<Wire>().unwrap().segments()[0];
+    /// let second = "U3,R10".parse::<Wire>().unwrap().segments()[1];
+    /// let third = "R3,L10".parse::<Wire>().unwrap().segments()[1];
+    /// assert_eq!(None, first.overlap(&second));
+    /// assert_eq!(Some((Point::new(0, 0), Point::new(3, 0))), first.overlap(&third));
+    /// ```
+    pub fn overlap(&self, other: &Segment) -> Option<(Point, Point)> {
+        let (low, high) = self.bounds();
+        let (other_low, other_high) = other.bounds();
+        let low = Point::new(low.x.max(other_low.x), low.y.max(other_low.y));
+        let high = Point::new(high.x.min(other_high.x), high.y.min(other_high.y));
+
+        if low.x <= high.x && low.y <= high.y {
+            Some((low, high))
+        } else {
+            None
+        }
+    }
+}
+
+/// ## Wire
+/// A path from the origin made of straight segments
+/// ### Examples
+/// ```
+/// use day3::wire::{Direction, Point, Wire};
+///
+/// let wire = "R8,U5,L5,D3".parse::<Wire>().unwrap();
+/// assert_eq!(4, wire.segments().len());
+/// assert_eq!(Direction::Up, wire.segments()[1].direction);
+/// assert_eq!(Point::new(3, 2), wire.end());
+/// assert_eq!(Some(20), wire.steps_to(Point::new(3, 3)));
+/// assert_eq!("R8,U5,L5,D3", wire.to_string());
+///
+/// assert!("R8,X5".parse::<Wire>().is_err());
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+pub struct Wire {
+    segments: Vec<Segment>,
+}
+
+impl Wire {
+    /// Parse a wire written on the given line of some input, counting from 1
+    pub fn parse_line(text: &str, line: usize) -> Result<Wire, WireError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(WireError::Empty { line });
+        }
+
+        let mut segments = Vec::new();
+        let mut position = Point::default();
+        let mut steps = 0;
+        for (index, step) in text.split(',').enumerate() {
+            let error = || WireError::BadStep {
+                line,
+                step: index + 1,
+                found: String::from(step),
+            };
+            let step = step.trim();
+            let mut letters = step.chars();
+            let direction = letters
+                .next()
+                .and_then(Direction::from_char)
+                .ok_or_else(error)?;
+            let length = i64::from(letters.as_str().parse::<u32>().map_err(|_| error())?);
+
+            let segment = Segment {
+                start: position,
+                direction,
+                length,
+                steps,
+            };
+            position = segment.end();
+            steps += length;
+            segments.push(segment);
+        }
+
+        Ok(Wire { segments })
+    }
+
+    /// Get the straight parts of the wire, in order from the origin
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Get where the wire ends
+    pub fn end(&self) -> Point {
+        self.segments
+            .last()
+            .map_or(Point::default(), |segment| segment.end())
+    }
+
+    /// Get the number of steps along the wire to the first time it reaches `point`
+    pub fn steps_to(&self, point: Point) -> Option<i64> {
+        self.segments
+            .iter()
+            .find_map(|segment| segment.steps_to(point))
+    }
+}
+
+impl Display for Wire {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        for (index, segment) in self.segments.iter().enumerate() {
+            let separator = if index == 0 { "" } else { "," };
+            write!(f, "{}{}{}", separator, segment.direction, segment.length)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Wire {
+    type Err = WireError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Wire::parse_line(s, 1)
+    }
+}
+
+/// Parse one wire from each line of `text`, ignoring blank lines
+pub fn parse_wires(text: &str) -> Result<Vec<Wire>, WireError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| Wire::parse_line(line, index + 1))
+        .collect()
+}
+
+/// ## Intersection
+/// A point other than the origin where two wires meet
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Intersection {
+    pub point: Point,
+    /// The indices of the wires, lowest first
+    pub wires: (usize, usize),
+    /// The number of steps both wires take to first reach the point, added together
+    pub steps: i64,
+}
+
+impl Intersection {
+    /// Get the Manhattan distance of the intersection from the origin
+    pub fn distance(&self) -> i64 {
+        self.point.distance()
+    }
+}
+
+/// ## Overlap
+/// Where a segment of one wire meets a segment of another, other than only at the origin.
+/// Segments that cross share a single point, while segments on the same line may share a
+/// stretch of points, which is kept whole rather than split into points.
+/// ### Examples
+/// ```
+/// use day3::wire::{overlaps, parse_wires, Point};
+///
+/// // The wires run alongside each other for four billion steps
+/// let wires = parse_wires("L3,R4000000000\nU1,D1,R4000000000").unwrap();
+/// let found = overlaps(&wires);
+/// assert_eq!(1, found.len());
+/// assert_eq!((Point::new(0, 0), Point::new(3_999_999_997, 0)), found[0].bounds);
+/// assert_eq!(Some(1), found[0].distance());
+/// assert_eq!(Some(3 + 4 + 2 + 1), found[0].steps());
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub struct Overlap {
+    /// The indices of the wires, lowest first
+    pub wires: (usize, usize),
+    /// The corners of the box covered by the shared points; the lowest x and y, then the highest
+    pub bounds: (Point, Point),
+    // The segments of each wire, in the same order as `wires`
+    segments: (Segment, Segment),
+}
+
+impl Overlap {
+    // Gets where segments of two different wires meet
+    pub(crate) fn between(first: (usize, &Segment), second: (usize, &Segment)) -> Option<Overlap> {
+        let (first, second) = match first.0.cmp(&second.0) {
+            Ordering::Less => (first, second),
+            Ordering::Greater => (second, first),
+            Ordering::Equal => return None,
+        };
+        let bounds = first.1.overlap(second.1)?;
+        if bounds == (Point::default(), Point::default()) {
+            return None;
+        }
+
+        Some(Overlap {
+            wires: (first.0, second.0),
+            bounds,
+            segments: (*first.1, *second.1),
+        })
+    }
+
+    /// Get every shared point, other than the origin
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let (low, high) = self.bounds;
+        (low.x..=high.x)
+            .flat_map(move |x| (low.y..=high.y).map(move |y| Point::new(x, y)))
+            .filter(|&point| point != Point::default())
+    }
+
+    /// Get the Manhattan distance to the closest shared point, other than the origin
+    pub fn distance(&self) -> Option<i64> {
+        self.candidates().map(Point::distance).min()
+    }
+
+    /// Get the fewest steps both wires take along these segments to a shared point other than
+    /// the origin, added together
+    pub fn steps(&self) -> Option<i64> {
+        self.candidates().map(|point| self.steps_to(point)).min()
+    }
+
+    // The combined steps along both segments to a shared point
+    fn steps_to(&self, point: Point) -> i64 {
+        let (first, second) = self.segments;
+        first.steps
+            + first.start.distance_to(point)
+            + second.steps
+            + second.start.distance_to(point)
+    }
+
+    // The shared points closest to the start of either segment or to the origin, and to each
+    // side of the origin. Both distance and steps change linearly along the shared points,
+    // except for turning once, so one of these is always the closest and one the fewest steps.
+    fn candidates(&self) -> impl Iterator<Item = Point> {
+        let (low, high) = self.bounds;
+        let targets = [
+            self.segments.0.start,
+            self.segments.1.start,
+            Point::new(0, 0),
+            Point::new(1, 0),
+            Point::new(-1, 0),
+            Point::new(0, 1),
+            Point::new(0, -1),
+        ];
+        IntoIterator::into_iter(targets)
+            .map(move |point| {
+                Point::new(point.x.clamp(low.x, high.x), point.y.clamp(low.y, high.y))
+            })
+            .filter(|&point| point != Point::default())
+    }
+}
+
+/// Find where segments of different wires meet by comparing every segment of each wire with
+/// every segment of the others, in order
+pub fn overlaps(wires: &[Wire]) -> Vec<Overlap> {
+    let mut found = Vec::new();
+    for (first, first_wire) in wires.iter().enumerate() {
+        for (second, second_wire) in wires.iter().enumerate().skip(first + 1) {
+            for first_segment in first_wire.segments() {
+                for second_segment in second_wire.segments() {
+                    found.extend(Overlap::between(
+                        (first, first_segment),
+                        (second, second_segment),
+                    ));
+                }
+            }
+        }
+    }
+
+    found.sort_unstable();
+    found
+}
+
+/// Find every point, other than the origin, where two of `wires` meet, by comparing every
+/// segment of each wire with every segment of the others. Wires that run alongside each other
+/// meet at every point they share, so this takes time and memory for each of them; use
+/// `overlaps` where wires may run alongside each other for long distances. The intersections
+/// are ordered by the wires, then the point.
+/// ### Examples
+/// ```
+/// use day3::wire::{intersections, parse_wires, Point};
+///
+/// let wires = parse_wires("R8,U5,L5,D3\nU7,R6,D4,L4\n").unwrap();
+/// let found = intersections(&wires);
+/// let points = found.iter().map(|found| found.point).collect::<Vec<_>>();
+/// assert_eq!(vec![Point::new(3, 3), Point::new(6, 5)], points);
+/// assert_eq!(Some(6), found.iter().map(|found| found.distance()).min());
+/// assert_eq!(Some(30), found.iter().map(|found| found.steps).min());
+/// ```
+pub fn intersections(wires: &[Wire]) -> Vec<Intersection> {
+    points(&overlaps(wires))
+}
+
+// Splits overlaps into the points they share, keeping the fewest combined steps to each point
+pub(crate) fn points(overlaps: &[Overlap]) -> Vec<Intersection> {
+    let mut found = BTreeMap::new();
+    for overlap in overlaps {
+        for point in overlap.points() {
+            let steps = overlap.steps_to(point);
+            found
+                .entry((overlap.wires, point))
+                .and_modify(|least: &mut i64| *least = steps.min(*least))
+                .or_insert(steps);
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|((wires, point), steps)| Intersection {
+            point,
+            wires,
+            steps,
+        })
+        .collect()
+}
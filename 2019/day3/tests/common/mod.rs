@@ -0,0 +1,14 @@
+//! Helpers shared by the integration tests and benchmarks.
+
+// A small linear congruential generator, so that the generated wires are reproducible
+pub struct Lcg(pub u64);
+
+impl Lcg {
+    pub fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        ((self.0 >> 33) % bound as u64) as usize
+    }
+}
@@ -0,0 +1,150 @@
+//! Differential tests checking that the sweep line finds exactly the intersections that
+//! comparing every pair of segments does.
+mod common;
+
+use common::Lcg;
+use day3::sweep;
+use day3::wire::{self, parse_wires, Overlap, Point, Wire};
+
+const CASES: usize = 500;
+
+// Generates a wire of short steps that often doubles back over itself and others
+fn random_wire(rng: &mut Lcg, steps: usize) -> Wire {
+    let path = (0..steps)
+        .map(|_| format!("{}{}", ['U', 'D', 'L', 'R'][rng.next(4)], rng.next(6)))
+        .collect::<Vec<_>>();
+    path.join(",").parse().unwrap()
+}
+
+#[test]
+fn matches_every_pair() {
+    let mut rng = Lcg(3);
+
+    for _ in 0..CASES {
+        let wires = (0..2 + rng.next(3))
+            .map(|_| {
+                let steps = 1 + rng.next(30);
+                random_wire(&mut rng, steps)
+            })
+            .collect::<Vec<_>>();
+        let wire_text = wires.iter().map(Wire::to_string).collect::<Vec<_>>();
+        let overlaps = sweep::overlaps(&wires);
+        assert_eq!(wire::overlaps(&wires), overlaps, "{:?}", wire_text);
+
+        // The closest and fewest steps taken from whole overlaps match those of every point
+        let found = sweep::intersections(&wires);
+        assert_eq!(wire::intersections(&wires), found, "{:?}", wire_text);
+        assert_eq!(
+            found.iter().map(|found| found.distance()).min(),
+            overlaps.iter().filter_map(Overlap::distance).min(),
+            "{:?}",
+            wire_text
+        );
+        assert_eq!(
+            found.iter().map(|found| found.steps).min(),
+            overlaps.iter().filter_map(Overlap::steps).min(),
+            "{:?}",
+            wire_text
+        );
+    }
+}
+
+#[test]
+fn long_overlaps() {
+    // Listing every shared point would take billions of entries
+    let wires =
+        parse_wires("R4000000000\nU1,D1,R4000000000\nD2,R4000000000,U2,L3999999999").unwrap();
+    let overlaps = sweep::overlaps(&wires);
+    let found = overlaps
+        .iter()
+        .map(|overlap| (overlap.wires, overlap.distance(), overlap.steps()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            ((0, 1), Some(1), Some(1 + 3)),
+            ((0, 2), Some(1), Some(1 + 8_000_000_003)),
+            (
+                (0, 2),
+                Some(4_000_000_000),
+                Some(4_000_000_000 + 4_000_000_004)
+            ),
+            ((1, 2), Some(1), Some(3 + 8_000_000_003)),
+            (
+                (1, 2),
+                Some(4_000_000_000),
+                Some(4_000_000_002 + 4_000_000_004)
+            ),
+        ],
+        found
+    );
+}
+
+#[test]
+fn collinear_overlaps() {
+    // Opposite directions along the same line, one inside the other
+    let wires = parse_wires("R10\nR7,L4").unwrap();
+    let points = sweep::intersections(&wires)
+        .into_iter()
+        .map(|found| (found.point, found.steps))
+        .collect::<Vec<_>>();
+    let expected = (1..=7)
+        .map(|x| (Point::new(x, 0), x + x))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, points);
+
+    // Vertical segments that overlap from (1, 3) to (1, 6)
+    let wires = parse_wires("U3,R1,U3\nR1,U6").unwrap();
+    let found = sweep::intersections(&wires);
+    let points = found.iter().map(|found| found.point).collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            Point::new(1, 3),
+            Point::new(1, 4),
+            Point::new(1, 5),
+            Point::new(1, 6)
+        ],
+        points
+    );
+
+    // Vertical segments on the same line that touch only where one ends and the other starts
+    let wires = parse_wires("U3\nR1,U5,L1,D2").unwrap();
+    let found = sweep::intersections(&wires)
+        .into_iter()
+        .map(|found| (found.point, found.steps))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![(Point::new(0, 3), 3 + 9)], found);
+    assert_eq!(wire::intersections(&wires), sweep::intersections(&wires));
+}
+
+#[test]
+fn touching_ends() {
+    // The first two wires meet only at their ends, and the third ends in a step of no length
+    // on the second
+    let wires = parse_wires("R4,U2\nU2,R4\nU1,R3,U1,R0").unwrap();
+    let found = sweep::intersections(&wires)
+        .into_iter()
+        .map(|found| (found.wires, found.point))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            ((0, 1), Point::new(4, 2)),
+            ((1, 2), Point::new(0, 1)),
+            ((1, 2), Point::new(3, 2)),
+        ],
+        found
+    );
+    assert_eq!(wire::intersections(&wires), sweep::intersections(&wires));
+}
+
+#[test]
+fn doubling_back_along_one_line() {
+    // Every segment of the first wire overlaps every other, but as they belong to the same
+    // wire none of those pairs are compared; the second wire touches the first only at (5, 0)
+    let first = vec!["R1000,L1000"; 20_000].join(",");
+    let wires = parse_wires(&format!("{}\nU1,R5,D1", first)).unwrap();
+    let found = sweep::overlaps(&wires)
+        .iter()
+        .map(|overlap| overlap.bounds)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![(Point::new(5, 0), Point::new(5, 0)); 40_000], found);
+}
@@ -0,0 +1,91 @@
+//! The published examples from problem 3, and wires beyond the two the puzzle uses.
+use day3::wire::{intersections, parse_wires, Direction, Intersection, Point, Wire, WireError};
+
+// Each pair of wires along with the closest distance and fewest combined steps to a crossing
+const EXAMPLES: [(&str, i64, i64); 3] = [
+    ("R8,U5,L5,D3\nU7,R6,D4,L4", 6, 30),
+    (
+        "R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83",
+        159,
+        610,
+    ),
+    (
+        "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51\nU98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
+        135,
+        410,
+    ),
+];
+
+#[test]
+fn examples() {
+    for &(input, distance, steps) in EXAMPLES.iter() {
+        let found = intersections(&parse_wires(input).unwrap());
+        assert_eq!(
+            Some(distance),
+            found.iter().map(Intersection::distance).min()
+        );
+        assert_eq!(Some(steps), found.iter().map(|found| found.steps).min());
+    }
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(
+        Err(WireError::BadStep {
+            line: 2,
+            step: 3,
+            found: String::from("X4"),
+        }),
+        parse_wires("R1\nU2,L3,X4")
+    );
+    assert_eq!(
+        Err(WireError::BadStep {
+            line: 1,
+            step: 1,
+            found: String::from("R-4"),
+        }),
+        "R-4".parse::<Wire>()
+    );
+    assert!("R".parse::<Wire>().is_err());
+    assert!("R1,,U2".parse::<Wire>().is_err());
+    assert_eq!(Err(WireError::Empty { line: 1 }), "".parse::<Wire>());
+
+    // Blank lines between wires are skipped, but still counted
+    let wires = parse_wires("R1\n\nU2\n").unwrap();
+    assert_eq!(2, wires.len());
+    assert_eq!(Direction::Up, wires[1].segments()[0].direction);
+}
+
+#[test]
+fn every_pair_of_wires() {
+    // A wire crossed by two others, which share most of their path
+    let wires = parse_wires("U3,R2\nR1,U2,L2\nR1,U2,L3,U2").unwrap();
+    let found = intersections(&wires)
+        .into_iter()
+        .map(|found| (found.wires, found.point, found.steps))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            ((0, 1), Point::new(0, 2), 2 + 4),
+            ((0, 2), Point::new(0, 2), 2 + 4),
+            ((1, 2), Point::new(-1, 2), 5 + 5),
+            ((1, 2), Point::new(0, 2), 4 + 4),
+            ((1, 2), Point::new(1, 0), 1 + 1),
+            ((1, 2), Point::new(1, 1), 2 + 2),
+            ((1, 2), Point::new(1, 2), 3 + 3),
+        ],
+        found
+    );
+}
+
+#[test]
+fn first_visit_counts() {
+    // The second wire reaches (2, 0) after 2 steps, then after 10 alongside the first
+    let wires = parse_wires("U1,R2,D3\nR4,U2,L2,D4").unwrap();
+    let found = intersections(&wires);
+    let crossing = found
+        .iter()
+        .find(|found| found.point == Point::new(2, 0))
+        .unwrap();
+    assert_eq!(4 + 2, crossing.steps);
+}
@@ -1,11 +1,16 @@
+use std::env;
 use std::error::Error;
 use std::io::Write;
 use std::path::Path;
 
+use intcode_computer::replay::{Record, Session};
 use intcode_computer::Machine;
 use text_io::{read, try_read, try_scan};
 
-// Input 1 for part 1, 5 for part 2
+const USAGE: &str = "usage: day5 [--record <FILE> | --replay <FILE>]";
+
+// Input 1 for part 1, 5 for part 2. A session can be saved with `--record` and run again
+// without typing the input with `--replay`.
 fn main() -> Result<(), Box<dyn Error>> {
     let path = Path::new("input5.txt");
     let mut machine = Machine::from_path(path)?;
@@ -13,10 +18,30 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut input_fn = || {
         print!("> ");
         std::io::stdout().flush().unwrap();
-        read!()
+        Some(read!())
     };
     let mut output_fn = |out| println!("= {}", out);
-    machine.execute(&mut input_fn, &mut output_fn);
+
+    let arguments = env::args().skip(1).collect::<Vec<_>>();
+    match arguments.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => machine.execute(&mut input_fn, &mut output_fn)?,
+        ["--record", session_path] => {
+            let (session, result) = machine.execute_recorded(&mut input_fn, &mut output_fn);
+            session.save(Path::new(session_path))?;
+            result?;
+        }
+        ["--replay", session_path] => {
+            let session = Session::load(Path::new(session_path))?;
+            machine.replay(&session)?;
+            for (_, record) in session.records() {
+                match record {
+                    Record::Input(value) => println!("> {}", value),
+                    Record::Output(value) => println!("= {}", value),
+                }
+            }
+        }
+        _ => return Err(USAGE.into()),
+    }
 
     Ok(())
 }
@@ -32,23 +32,13 @@ impl Body {
     }
 
     fn parent(&self) -> Option<Rc<RefCell<Body>>> {
-        if let Some(parent) = &self.parent {
-            Some(parent.clone())
-        } else {
-            None
-        }
+        self.parent.clone()
     }
 
     fn children(&self) -> Vec<Rc<RefCell<Body>>> {
         self.children
             .iter()
-            .filter_map(|child| {
-                if let Some(child) = child.upgrade() {
-                    Some(child)
-                } else {
-                    None
-                }
-            })
+            .filter_map(|child| child.upgrade())
             .collect()
     }
 }
@@ -0,0 +1,106 @@
+// Compiles the test fixtures, so that the tests can check the generated code against the
+// interpreter
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use intcode_computer::compiler::{compile, CompileError};
+use intcode_computer::loader::parse;
+
+// Published example programs from days 2, 5 and 9
+const EXAMPLES: &[(&str, &str)] = &[
+    ("day2_example", "1,9,10,3,2,3,11,0,99,30,40,50"),
+    ("day2_add", "1,0,0,0,99"),
+    ("day2_multiply", "2,3,0,3,99"),
+    ("day2_square", "2,4,4,5,99,0"),
+    ("day2_patched", "1,1,1,4,99,5,6,0,99"),
+    (
+        "day2_shaped",
+        "1,0,0,3,1,1,2,3,1,3,4,3,1,5,0,3,2,1,10,19,1,19,5,23,2,23,9,27,1,5,27,31,1,31,2,0,99",
+    ),
+    ("day5_echo", "3,0,4,0,99"),
+    ("day5_modes", "1002,4,3,4,33"),
+    ("day5_negative", "1101,100,-1,4,0"),
+    ("day5_equal_position", "3,9,8,9,10,9,4,9,99,-1,8"),
+    ("day5_less_position", "3,9,7,9,10,9,4,9,99,-1,8"),
+    ("day5_equal_immediate", "3,3,1108,-1,8,3,4,3,99"),
+    ("day5_less_immediate", "3,3,1107,-1,8,3,4,3,99"),
+    (
+        "day5_jump_position",
+        "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9",
+    ),
+    ("day5_jump_immediate", "3,3,1105,-1,9,1101,0,0,12,4,12,99,1"),
+    (
+        "day5_larger",
+        "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,\
+         1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99",
+    ),
+    (
+        "day9_quine",
+        "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99",
+    ),
+    ("day9_large_product", "1102,34915192,34915192,7,4,7,99,0"),
+    ("day9_large_literal", "104,1125899906842624,99"),
+];
+
+// Synthetic programs written to resemble the day 2 and day 5 puzzle inputs, which are not
+// checked in, and one that writes far beyond its image. They live under `fixtures`.
+const FILES: &[&str] = &["synthetic_day2", "synthetic_day5", "high_write"];
+
+fn main() {
+    println!("cargo:rerun-if-changed=fixtures");
+
+    let mut programs = EXAMPLES
+        .iter()
+        .map(|&(name, text)| (name, text.to_string()))
+        .collect::<Vec<_>>();
+    for &name in FILES {
+        let path = Path::new("fixtures").join(name).with_extension("txt");
+        let text = fs::read_to_string(&path)
+            .unwrap_or_else(|why| panic!("couldn't read {}: {}", path.display(), why));
+        programs.push((name, text));
+    }
+
+    let mut modules = String::new();
+    let mut table = String::from("const FIXTURES: &[Fixture] = &[\n");
+    for (name, text) in programs {
+        let program = parse::<isize>(&text).expect("fixture is not a valid program");
+        let run = match compile(&program, name) {
+            Ok(source) => {
+                modules.push_str(&source);
+                format!(
+                    "Some(|image, inputs| {{
+            let mut memory = {name}::Memory::new(image);
+            let mut inputs = inputs.iter().copied();
+            let mut outputs = Vec::new();
+            let result =
+                match {name}::run(&mut memory, &mut || inputs.next(), &mut |out| outputs.push(out)) {{
+                    Ok(()) => Ok(outputs),
+                    Err({name}::Stop::Unsupported(address)) => Err(Stopped::Unsupported(address)),
+                    Err({name}::Stop::Fault(why)) => Err(Stopped::Fault(why)),
+                }};
+            Ran {{
+                result,
+                image: memory.image().to_vec(),
+                beyond: memory.beyond().collect(),
+            }}
+        }})",
+                    name = name
+                )
+            }
+            Err(CompileError::SelfModifying { .. }) => String::from("None"),
+            Err(why) => panic!("{}", why),
+        };
+        writeln!(
+            table,
+            "    Fixture {{\n        name: {:?},\n        image: &{:?},\n        run: {},\n    }},",
+            name, program, run
+        )
+        .unwrap();
+    }
+    table.push_str("];\n");
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("fixtures.rs"), modules + &table).unwrap();
+}
@@ -0,0 +1,60 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use intcode_computer::compiler::compile;
+use intcode_computer::Machine;
+
+const USAGE: &str = "\
+usage: intcode_compiler <PROGRAM> [options]
+  --name <NAME>      the name of the generated module, `program` by default
+  --output <FILE>    write the generated source to a file instead of stdout";
+
+struct Options {
+    program: String,
+    name: String,
+    output: Option<String>,
+}
+
+impl Options {
+    fn parse(arguments: &[String]) -> Result<Options, String> {
+        let mut program = None;
+        let mut name = None;
+        let mut output = None;
+        let mut arguments = arguments.iter();
+        while let Some(argument) = arguments.next() {
+            let target = match argument.as_str() {
+                "--name" => &mut name,
+                "--output" => &mut output,
+                _ if program.is_none() && !argument.starts_with("--") => {
+                    program = Some(argument.clone());
+                    continue;
+                }
+                _ => return Err(USAGE.to_string()),
+            };
+            *target = Some(arguments.next().ok_or(USAGE)?.clone());
+        }
+
+        Ok(Options {
+            program: program.ok_or(USAGE)?,
+            name: name.unwrap_or_else(|| String::from("program")),
+            output,
+        })
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let arguments = env::args().skip(1).collect::<Vec<_>>();
+    let options = Options::parse(&arguments)?;
+
+    let machine = Machine::from_path(Path::new(&options.program))?;
+    let source = compile(machine.memory().image(), &options.name)?;
+
+    match &options.output {
+        Some(path) => fs::write(path, source)?,
+        None => print!("{}", source),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! Checks programs compiled to Rust against the interpreter. The fixtures are compiled by the
+//! build script from the published examples and the programs checked in under `fixtures`.
+//! Those include synthetic programs in the style of the day 2 and day 5 puzzle inputs; the real
+//! inputs are not checked in, so these tests do not run them.
+use intcode_computer::{IntcodeError, Machine};
+
+// How a compiled program stopped before reaching the end
+#[derive(PartialEq, Debug)]
+enum Stopped {
+    Unsupported(usize),
+    Fault(String),
+}
+
+// The outputs of a compiled program and the memory it left behind
+struct Ran {
+    result: Result<Vec<isize>, Stopped>,
+    image: Vec<isize>,
+    // Cells written beyond the image
+    beyond: Vec<(usize, isize)>,
+}
+
+// Runs a compiled program on an image with a list of inputs
+type Run = fn(Vec<isize>, &[isize]) -> Ran;
+
+struct Fixture {
+    name: &'static str,
+    image: &'static [isize],
+    // `None` when the compiler refused the program
+    run: Option<Run>,
+}
+
+include!(concat!(env!("OUT_DIR"), "/fixtures.rs"));
+
+const INPUTS: &[&[isize]] = &[&[], &[-3], &[0], &[1], &[5], &[7], &[8], &[9], &[1, 2, 3]];
+
+fn fixture(name: &str) -> &'static Fixture {
+    FIXTURES
+        .iter()
+        .find(|fixture| fixture.name == name)
+        .unwrap()
+}
+
+fn execute(memory: Vec<isize>, inputs: &[isize]) -> (Machine, Result<Vec<isize>, IntcodeError>) {
+    let mut machine = Machine::new(memory);
+    let mut inputs = inputs.iter().copied();
+    let mut outputs = Vec::new();
+    let result = machine.execute(&mut || inputs.next(), &mut |out| outputs.push(out));
+    (machine, result.map(|()| outputs))
+}
+
+// Runs the compiled program and checks that the result matches the interpreter's, unless it is
+// refused or stops with `Stopped::Unsupported`, in which case the interpreter's result is used
+fn check(fixture: &Fixture, memory: Vec<isize>, inputs: &[isize]) -> Option<Vec<isize>> {
+    let (machine, expected) = execute(memory.clone(), inputs);
+
+    let ran = match fixture.run {
+        Some(run) => run(memory.clone(), inputs),
+        None => Ran {
+            result: Err(Stopped::Unsupported(0)),
+            image: memory.clone(),
+            beyond: Vec::new(),
+        },
+    };
+
+    match ran.result {
+        Ok(outputs) => {
+            assert_eq!(Ok(&outputs), expected.as_ref(), "{}", fixture.name);
+            assert_eq!(machine.memory().image(), &ran.image[..], "{}", fixture.name);
+            for &(address, value) in &ran.beyond {
+                assert_eq!(Ok(value), machine.memory().get(address), "{}", fixture.name);
+            }
+            Some(outputs)
+        }
+        Err(Stopped::Fault(why)) => {
+            assert!(expected.is_err(), "{} faulted: {}", fixture.name, why);
+            None
+        }
+        // Falling back means running on a `Machine`, which is what gave `expected`
+        Err(Stopped::Unsupported(_)) => expected.ok(),
+    }
+}
+
+#[test]
+fn matches_execute() {
+    for fixture in FIXTURES {
+        for inputs in INPUTS {
+            // Nothing changes the compiled programs' code, so they never need to fall back
+            if let Some(run) = fixture.run {
+                let ran = run(fixture.image.to_vec(), inputs);
+                assert!(
+                    !matches!(ran.result, Err(Stopped::Unsupported(_))),
+                    "{}",
+                    fixture.name
+                );
+            }
+            check(fixture, fixture.image.to_vec(), inputs);
+        }
+    }
+}
+
+#[test]
+fn examples() {
+    let day2 = fixture("day2_example");
+    let ran = (day2.run.unwrap())(day2.image.to_vec(), &[]);
+    assert_eq!(Ok(vec![]), ran.result);
+    assert_eq!(3500, ran.image[0]);
+
+    let larger = fixture("day5_larger");
+    for (input, output) in [(7, 999), (8, 1000), (9, 1001)].iter() {
+        assert_eq!(
+            Some(vec![*output]),
+            check(larger, larger.image.to_vec(), &[*input])
+        );
+    }
+
+    let quine = fixture("day9_quine");
+    assert_eq!(
+        Some(quine.image.to_vec()),
+        check(quine, quine.image.to_vec(), &[])
+    );
+}
+
+#[test]
+fn noun_and_verb() {
+    let day2 = FIXTURES
+        .iter()
+        .filter(|fixture| fixture.name == "day2_shaped" || fixture.name == "synthetic_day2");
+    for fixture in day2 {
+        for noun in (0..=99).step_by(11) {
+            for verb in (0..=99).step_by(7) {
+                let mut memory = fixture.image.to_vec();
+                memory[1] = noun;
+                memory[2] = verb;
+                check(fixture, memory, &[]);
+            }
+        }
+    }
+}
+
+#[test]
+fn self_modifying() {
+    // Both halt by overwriting the instruction that follows them
+    assert!(fixture("day5_modes").run.is_none());
+    assert!(fixture("day5_negative").run.is_none());
+    assert_eq!(
+        Some(vec![]),
+        check(
+            fixture("day5_negative"),
+            fixture("day5_negative").image.to_vec(),
+            &[]
+        )
+    );
+
+    // Changing an opcode before running stops the compiled program at that instruction
+    let echo = fixture("day5_echo");
+    let mut memory = echo.image.to_vec();
+    memory[2] = 104;
+    assert_eq!(
+        Err(Stopped::Unsupported(2)),
+        (echo.run.unwrap())(memory.clone(), &[5]).result
+    );
+    assert_eq!(Some(vec![0]), check(echo, memory, &[5]));
+}
+
+#[test]
+fn synthetic_programs() {
+    let day5 = fixture("synthetic_day5");
+    assert!(day5.run.is_some());
+    assert_eq!(
+        Some(vec![0, 0, 0, 42007]),
+        check(day5, day5.image.to_vec(), &[1])
+    );
+    assert_eq!(Some(vec![7566]), check(day5, day5.image.to_vec(), &[5]));
+
+    let day2 = fixture("synthetic_day2");
+    let mut memory = day2.image.to_vec();
+    memory[1] = 12;
+    memory[2] = 2;
+    let ran = (day2.run.unwrap())(memory.clone(), &[]);
+    let (machine, _) = execute(memory, &[]);
+    assert_eq!(machine.result(), ran.image[0]);
+}
+
+#[test]
+fn writes_far_beyond_the_image() {
+    // Stores near the memory limit and beyond the contiguous memory without allocating the
+    // cells in between
+    let high = fixture("high_write");
+    let ran = (high.run.unwrap())(high.image.to_vec(), &[]);
+    assert_eq!(Ok(vec![42, 3]), ran.result);
+    assert_eq!(high.image, &ran.image[..]);
+    assert_eq!(vec![(1_000_000, 3), ((1 << 30) - 1, 42)], ran.beyond);
+    assert_eq!(Some(vec![42, 3]), check(high, high.image.to_vec(), &[]));
+}
@@ -0,0 +1,150 @@
+//! # Adapters
+//! Ways to connect the input and output of a `Machine` without writing closures.
+//!
+//! A machine can be fed from any iterator with its outputs collected into a `Vec`, run against
+//! a pair of `mpsc` channels so that it can live on its own thread, or driven with lines of
+//! ASCII text for programs that talk in characters.
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread;
+use std::thread::JoinHandle;
+
+use crate::{IntcodeError, Machine, Word};
+
+impl<W: Word> Machine<W> {
+    /// Execute the program until it halts or faults, taking input from `inputs` and returning
+    /// the values output
+    /// ### Examples
+    /// ```
+    /// use intcode_computer::Machine;
+    ///
+    /// // Output 1 if the input is 8, else 0
+    /// let program = vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+    /// assert_eq!(Ok(vec![1]), Machine::new(program.clone()).run_with_inputs(vec![8]));
+    /// assert_eq!(Ok(vec![0]), Machine::new(program).run_with_inputs(Some(7)));
+    /// ```
+    pub fn run_with_inputs<I>(&mut self, inputs: I) -> Result<Vec<W>, IntcodeError>
+    where
+        I: IntoIterator<Item = W>,
+    {
+        let mut inputs = inputs.into_iter();
+        let mut outputs = Vec::new();
+        self.execute(&mut || inputs.next(), &mut |out| outputs.push(out))?;
+
+        Ok(outputs)
+    }
+
+    /// Execute the program until it halts or faults, reading input from `inputs` and sending
+    /// each value output to `outputs`. Waits when no input is available, and faults with
+    /// `IntcodeError::InputExhausted` once every sender for `inputs` has been dropped. Outputs
+    /// are discarded if `outputs` has no receiver.
+    pub fn run_on_channels(
+        &mut self,
+        inputs: &Receiver<W>,
+        outputs: &Sender<W>,
+    ) -> Result<(), IntcodeError> {
+        self.execute(&mut || inputs.recv().ok(), &mut |out| {
+            let _ = outputs.send(out);
+        })
+    }
+
+    /// Run the machine on its own thread with `Machine::run_on_channels`. The thread returns
+    /// the machine along with how it stopped.
+    /// ### Examples
+    /// ```
+    /// use std::sync::mpsc::channel;
+    ///
+    /// use intcode_computer::{ExecutionState, Machine};
+    ///
+    /// // Output double the input, until the input is 0
+    /// let program = vec![3, 15, 1006, 15, 14, 1002, 15, 2, 15, 4, 15, 1105, 1, 0, 99, 0];
+    /// let (input, machine_input) = channel();
+    /// let (machine_output, output) = channel();
+    /// let handle = Machine::new(program).spawn(machine_input, machine_output);
+    ///
+    /// input.send(21).unwrap();
+    /// assert_eq!(Ok(42), output.recv());
+    /// input.send(0).unwrap();
+    ///
+    /// let (machine, result) = handle.join().unwrap();
+    /// assert_eq!(Ok(()), result);
+    /// assert_eq!(&ExecutionState::Halted, machine.exec_state());
+    /// ```
+    pub fn spawn(
+        mut self,
+        inputs: Receiver<W>,
+        outputs: Sender<W>,
+    ) -> JoinHandle<(Machine<W>, Result<(), IntcodeError>)> {
+        thread::spawn(move || {
+            let result = self.run_on_channels(&inputs, &outputs);
+            (self, result)
+        })
+    }
+}
+
+impl Machine {
+    /// Execute the program until it halts or faults, taking each of `lines` as input followed
+    /// by a newline, and returning the output as text. Output values that are not ASCII are
+    /// written as decimal numbers on their own line.
+    /// ### Examples
+    /// ```
+    /// use intcode_computer::Machine;
+    ///
+    /// // Echo a line of two characters then output a large number
+    /// let program = vec![3, 0, 4, 0, 3, 0, 4, 0, 3, 0, 4, 0, 104, 12345, 99];
+    /// let output = Machine::new(program).run_ascii(vec!["hi"]).unwrap();
+    /// assert_eq!("hi\n12345\n", output);
+    /// ```
+    pub fn run_ascii<I, S>(&mut self, lines: I) -> Result<String, IntcodeError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let outputs = self.run_with_inputs(encode_lines(lines))?;
+        Ok(decode(&outputs))
+    }
+}
+
+/// Convert `text` into the input codes for each of its bytes
+pub fn encode(text: &str) -> Vec<isize> {
+    text.bytes().map(isize::from).collect()
+}
+
+/// Convert each of `lines` into input codes, ending each with a newline
+pub fn encode_lines<I, S>(lines: I) -> Vec<isize>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    lines
+        .into_iter()
+        .flat_map(|line| {
+            let mut codes = encode(line.as_ref());
+            codes.push(isize::from(b'\n'));
+            codes
+        })
+        .collect()
+}
+
+/// Convert output codes into text. Values that are not ASCII are written as decimal numbers on
+/// their own line.
+pub fn decode(outputs: &[isize]) -> String {
+    let mut text = String::new();
+    for &out in outputs {
+        decode_into(&mut text, out);
+    }
+    text
+}
+
+/// Append the text for a single output code to `text`, as `decode` does
+pub fn decode_into(text: &mut String, out: isize) {
+    match out {
+        0..=127 => text.push(out as u8 as char),
+        _ => {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(&out.to_string());
+            text.push('\n');
+        }
+    }
+}
@@ -0,0 +1,303 @@
+//! # Assembler
+//! Assembles a text format into an intcode program that can be loaded with `Machine::new`.
+//!
+//! Each line holds an optional label, followed by an instruction or a `data` directive.
+//! Anything after a `;` is a comment.
+//!
+//! ```text
+//! loop:   OUT [counter]
+//!         ADD [counter], #-1, [counter]
+//!         JNZ [counter], #loop
+//!         HLT
+//! counter: data 3
+//! ```
+//!
+//! Operands use the same notation as the disassembler; `[12]` for position mode, `#12` for
+//! immediate mode and `[rb+12]` for relative mode. Anywhere a number is expected a label may be
+//! used instead, optionally with an offset such as `label+1`, and it is replaced by the address
+//! of the label.
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::instruction::{Instruction, Mnemonic, Mode, Operand};
+
+/// ## AsmErrorKind
+/// The reasons a line of assembly can be rejected
+#[derive(PartialEq, Debug, Clone)]
+pub enum AsmErrorKind {
+    /// The mnemonic is not a known instruction or directive
+    UnknownMnemonic(String),
+    /// The instruction was given the wrong number of operands
+    WrongOperandCount { expected: usize, found: usize },
+    /// The operand could not be parsed
+    BadOperand(String),
+    /// An operand that is written to was given in immediate mode
+    ImmediateWrite(String),
+    /// The label was defined more than once
+    DuplicateLabel(String),
+    /// The label is used but never defined
+    UndefinedLabel(String),
+}
+
+/// ## AsmError
+/// A line of assembly that could not be assembled
+#[derive(PartialEq, Debug, Clone)]
+pub struct AsmError {
+    /// The line number, starting from 1
+    pub line: usize,
+    pub kind: AsmErrorKind,
+}
+
+impl Display for AsmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            AsmErrorKind::UnknownMnemonic(mnemonic) => write!(f, "unknown mnemonic {}", mnemonic),
+            AsmErrorKind::WrongOperandCount { expected, found } => {
+                write!(f, "expected {} operands but found {}", expected, found)
+            }
+            AsmErrorKind::BadOperand(operand) => write!(f, "bad operand {}", operand),
+            AsmErrorKind::ImmediateWrite(operand) => {
+                write!(
+                    f,
+                    "operand {} is written to so cannot be immediate",
+                    operand
+                )
+            }
+            AsmErrorKind::DuplicateLabel(label) => write!(f, "label {} is already defined", label),
+            AsmErrorKind::UndefinedLabel(label) => write!(f, "label {} is not defined", label),
+        }
+    }
+}
+
+impl Error for AsmError {}
+
+#[derive(Debug)]
+enum Value {
+    Number(isize),
+    Label(String, isize),
+}
+
+#[derive(Debug)]
+enum Item {
+    Instruction(Mnemonic, Vec<(Mode, Value)>),
+    Data(Vec<Value>),
+}
+
+/// Assemble `source` into an intcode program
+/// ### Examples
+/// ```
+/// use intcode_computer::asm::assemble;
+/// use intcode_computer::Machine;
+///
+/// let program = assemble(
+///     "
+///     loop:   OUT [counter]
+///             ADD [counter], #-1, [counter]
+///             JNZ [counter], #loop
+///             HLT
+///     counter: data 3
+///     ",
+/// )
+/// .unwrap();
+/// assert_eq!(vec![4, 10, 1001, 10, -1, 10, 1005, 10, 0, 99, 3], program);
+///
+/// let mut outputs = Vec::new();
+/// Machine::new(program)
+///     .execute(&mut || None, &mut |out| outputs.push(out))
+///     .unwrap();
+/// assert_eq!(vec![3, 2, 1], outputs);
+/// ```
+///
+/// Errors report the line they occurred on
+/// ```
+/// use intcode_computer::asm::{assemble, AsmErrorKind};
+///
+/// let error = assemble("IN [0]\nADD #1, #2, #3").unwrap_err();
+/// assert_eq!(2, error.line);
+/// assert_eq!(AsmErrorKind::ImmediateWrite(String::from("#3")), error.kind);
+/// ```
+pub fn assemble(source: &str) -> Result<Vec<isize>, AsmError> {
+    let mut labels = HashMap::new();
+    let mut items = Vec::new();
+    let mut address = 0;
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |kind| AsmError {
+            line: line_number,
+            kind,
+        };
+
+        let mut rest = line.split(';').next().unwrap_or("").trim();
+        while let Some((label, remainder)) = split_label(rest) {
+            if labels.insert(label.to_string(), address).is_some() {
+                return Err(error(AsmErrorKind::DuplicateLabel(label.to_string())));
+            }
+            rest = remainder;
+        }
+
+        if rest.is_empty() {
+            continue;
+        }
+
+        let (name, operands) = match rest.find(char::is_whitespace) {
+            Some(split) => (&rest[..split], rest[split..].trim()),
+            None => (rest, ""),
+        };
+        let operands = if operands.is_empty() {
+            Vec::new()
+        } else {
+            operands.split(',').map(str::trim).collect::<Vec<_>>()
+        };
+
+        let item = if name.eq_ignore_ascii_case("data") {
+            let values = operands
+                .iter()
+                .map(|operand| {
+                    parse_value(operand)
+                        .ok_or_else(|| error(AsmErrorKind::BadOperand(operand.to_string())))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            address += values.len();
+            Item::Data(values)
+        } else {
+            let mnemonic = Mnemonic::from_name(name)
+                .ok_or_else(|| error(AsmErrorKind::UnknownMnemonic(name.to_string())))?;
+            if operands.len() != mnemonic.arity() {
+                return Err(error(AsmErrorKind::WrongOperandCount {
+                    expected: mnemonic.arity(),
+                    found: operands.len(),
+                }));
+            }
+
+            let operands = operands
+                .iter()
+                .zip(mnemonic.writes())
+                .map(|(operand, &writes)| {
+                    let (mode, value) = parse_operand(operand)
+                        .ok_or_else(|| error(AsmErrorKind::BadOperand(operand.to_string())))?;
+                    if writes && mode == Mode::Immediate {
+                        return Err(error(AsmErrorKind::ImmediateWrite(operand.to_string())));
+                    }
+                    Ok((mode, value))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            address += mnemonic.arity() + 1;
+            Item::Instruction(mnemonic, operands)
+        };
+
+        items.push((line_number, item));
+    }
+
+    let mut program = Vec::with_capacity(address);
+    for (line, item) in items {
+        let resolve = |value: &Value| match value {
+            Value::Number(number) => Ok(*number),
+            Value::Label(label, offset) => match labels.get(label) {
+                Some(&address) => (address as isize)
+                    .checked_add(*offset)
+                    .ok_or_else(|| AsmError {
+                        line,
+                        kind: AsmErrorKind::BadOperand(format!("{}{:+}", label, offset)),
+                    }),
+                None => Err(AsmError {
+                    line,
+                    kind: AsmErrorKind::UndefinedLabel(label.clone()),
+                }),
+            },
+        };
+
+        match item {
+            Item::Instruction(mnemonic, operands) => {
+                let operands = operands
+                    .iter()
+                    .map(|(mode, value)| {
+                        Ok(Operand {
+                            mode: *mode,
+                            value: resolve(value)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                program.extend(Instruction { mnemonic, operands }.encode());
+            }
+            Item::Data(values) => {
+                for value in &values {
+                    program.push(resolve(value)?);
+                }
+            }
+        }
+    }
+
+    Ok(program)
+}
+
+// Splits a leading `label:` from a line
+fn split_label(line: &str) -> Option<(&str, &str)> {
+    let colon = line.find(':')?;
+    let label = line[..colon].trim();
+    if is_label(label) {
+        Some((label, line[colon + 1..].trim()))
+    } else {
+        None
+    }
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn parse_operand(operand: &str) -> Option<(Mode, Value)> {
+    if let Some(value) = operand.strip_prefix('#') {
+        return Some((Mode::Immediate, parse_value(value)?));
+    }
+
+    let inner = operand.strip_prefix('[')?.strip_suffix(']')?.trim();
+    if inner
+        .get(..2)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("rb"))
+    {
+        let offset = inner[2..].trim();
+        let value = if offset.is_empty() {
+            Value::Number(0)
+        } else if let Some(offset) = offset.strip_prefix('+') {
+            parse_value(offset)?
+        } else if offset.starts_with('-') {
+            Value::Number(offset.replace(' ', "").parse().ok()?)
+        } else {
+            return Some((Mode::Position, parse_value(inner)?));
+        };
+        return Some((Mode::Relative, value));
+    }
+
+    Some((Mode::Position, parse_value(inner)?))
+}
+
+fn parse_value(value: &str) -> Option<Value> {
+    let value = value.trim();
+    if let Ok(number) = value.parse() {
+        return Some(Value::Number(number));
+    }
+
+    let (label, offset) = match value.rfind(&['+', '-'][..]) {
+        Some(split) if split > 0 => (
+            value[..split].trim(),
+            value[split..].replace(' ', "").parse().ok()?,
+        ),
+        _ => (value, 0),
+    };
+
+    if is_label(label) {
+        Some(Value::Label(label.to_string(), offset))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,411 @@
+//! # Compiler
+//! Ahead-of-time translation of intcode programs into Rust source.
+//!
+//! Every instruction reachable from address 0 is compiled into one arm of a `match ip`
+//! dispatch loop. The opcode and parameter modes are fixed when compiling, but parameters are
+//! still read from memory, so a program whose operands are patched before or during the run,
+//! as day 2 does with its noun and verb, behaves as it would on a `Machine`.
+//!
+//! Programs that overwrite one of their instructions and then execute it can not be compiled
+//! this way. A write to a fixed address that can be followed by executing that address is
+//! refused by `compile`. Any other change to an executed opcode, or a jump to an address that
+//! was not compiled, is caught when the program runs and stops it with `Stop::Unsupported`,
+//! so that the caller can fall back to a `Machine`.
+//!
+//! The generated module contains:
+//! - `Stop`, an enum of `Unsupported(usize)` and `Fault(String)`
+//! - `Memory`, which stores cells near the start of memory in a vector and any written far
+//!   beyond it in a map, so that a single distant write does not allocate everything before it
+//! - `IMAGE`, the program as compiled
+//! - `run(memory, input_fn, output_fn)`, which runs the program on `memory`
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter, Write};
+
+use crate::instruction::{Instruction, Mnemonic, Mode, Operand};
+
+const WORDS_PER_LINE: usize = 16;
+
+// The runtime support shared by every compiled program
+const PRELUDE: &str = r#"    use std::collections::BTreeMap;
+
+    /// Why a compiled program stopped before reaching the end
+    #[derive(PartialEq, Debug, Clone)]
+    pub enum Stop {
+        /// The instruction at this address was changed or never compiled; run the program on
+        /// a `Machine` instead
+        Unsupported(usize),
+        /// The program faulted
+        Fault(String),
+    }
+
+    // The largest address the program may write to
+    const MEMORY_LIMIT: isize = 1 << 30;
+
+    // Addresses below this are kept in the image, as a `Machine` keeps them in its dense memory
+    const DENSE_LIMIT: usize = 1 << 16;
+
+    /// The memory a compiled program runs on. Cells close to the start are held in a vector
+    /// laid out like `Machine::memory().image()`, and cells written beyond it in a map.
+    #[derive(PartialEq, Debug, Clone, Default)]
+    pub struct Memory {
+        image: Vec<isize>,
+        beyond: BTreeMap<usize, isize>,
+    }
+
+    impl Memory {
+        /// Create a new `Memory` holding `image` from address 0
+        pub fn new(image: Vec<isize>) -> Memory {
+            Memory {
+                image,
+                beyond: BTreeMap::new(),
+            }
+        }
+
+        /// Get the contiguous part of memory
+        pub fn image(&self) -> &[isize] {
+            &self.image
+        }
+
+        /// Get the cells written beyond the contiguous part of memory, in address order
+        pub fn beyond(&self) -> impl Iterator<Item = (usize, isize)> + '_ {
+            self.beyond.iter().map(|(&address, &value)| (address, value))
+        }
+
+        /// Read the value stored at `address`
+        pub fn get(&self, address: usize) -> isize {
+            match self.image.get(address) {
+                Some(&value) => value,
+                None => self.beyond.get(&address).copied().unwrap_or(0),
+            }
+        }
+
+        /// Store `value` at `address`
+        pub fn set(&mut self, address: usize, value: isize) {
+            if address < self.image.len() {
+                self.image[address] = value;
+            } else if address < DENSE_LIMIT {
+                self.image.resize(address + 1, 0);
+                self.image[address] = value;
+            } else {
+                self.beyond.insert(address, value);
+            }
+        }
+    }
+
+    fn load(memory: &Memory, address: isize) -> Result<isize, Stop> {
+        if address < 0 {
+            return Err(Stop::Fault(format!("negative address {}", address)));
+        }
+        Ok(memory.get(address as usize))
+    }
+
+    fn store(memory: &mut Memory, address: isize, value: isize) -> Result<(), Stop> {
+        if address < 0 {
+            return Err(Stop::Fault(format!("negative address {}", address)));
+        }
+        if address >= MEMORY_LIMIT {
+            return Err(Stop::Fault(format!("address {} is beyond the memory limit", address)));
+        }
+        memory.set(address as usize, value);
+        Ok(())
+    }
+
+    fn offset(relative_base: isize, value: isize, ip: usize) -> Result<isize, Stop> {
+        relative_base
+            .checked_add(value)
+            .ok_or_else(|| Stop::Fault(format!("arithmetic overflow at address {}", ip)))
+    }
+
+    fn jump(target: isize) -> Result<usize, Stop> {
+        if target < 0 {
+            return Err(Stop::Fault(format!("jump to negative address {}", target)));
+        }
+        Ok(target as usize)
+    }
+
+    fn check(memory: &Memory, ip: usize, opcode: isize) -> Result<(), Stop> {
+        if load(memory, ip as isize)? != opcode {
+            return Err(Stop::Unsupported(ip));
+        }
+        Ok(())
+    }
+"#;
+
+/// ## CompileError
+/// The reasons a program can not be compiled
+#[derive(PartialEq, Debug, Clone)]
+pub enum CompileError {
+    /// The instruction at `writer` writes to the opcode of the instruction at `target`
+    SelfModifying { writer: usize, target: usize },
+    /// The name given for the generated module is not a Rust identifier
+    BadName(String),
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            CompileError::SelfModifying { writer, target } => write!(
+                f,
+                "the instruction at address {} overwrites the instruction at address {}",
+                writer, target
+            ),
+            CompileError::BadName(name) => write!(f, "{} is not a valid module name", name),
+        }
+    }
+}
+
+impl Error for CompileError {}
+
+// Finds every instruction reachable from address 0 by falling through or by jumping to a
+// fixed target, along with the addresses control can pass to from each address reached.
+// Addresses that do not hold an instruction are reached but have no successors.
+fn reachable(program: &[isize]) -> (BTreeMap<usize, Instruction>, BTreeMap<usize, Vec<usize>>) {
+    let mut instructions = BTreeMap::new();
+    let mut successors = BTreeMap::new();
+    let mut pending = vec![0];
+
+    while let Some(address) = pending.pop() {
+        if successors.contains_key(&address) {
+            continue;
+        }
+        let instruction = match Instruction::decode(program, address) {
+            Some(instruction) => instruction,
+            None => {
+                successors.insert(address, Vec::new());
+                continue;
+            }
+        };
+
+        let next = address + instruction.size();
+        let targets = match instruction.mnemonic {
+            Mnemonic::Hlt => Vec::new(),
+            Mnemonic::Jnz | Mnemonic::Jz => {
+                let target = instruction.operands[1];
+                if target.mode == Mode::Immediate && target.value >= 0 {
+                    vec![next, target.value as usize]
+                } else {
+                    vec![next]
+                }
+            }
+            _ => vec![next],
+        };
+        pending.extend(&targets);
+        successors.insert(address, targets);
+        instructions.insert(address, instruction);
+    }
+
+    (instructions, successors)
+}
+
+// Whether control can pass from `from` to `target` by the paths found by `reachable`
+fn reaches(successors: &BTreeMap<usize, Vec<usize>>, from: usize, target: usize) -> bool {
+    let mut seen = BTreeSet::new();
+    let mut pending = successors.get(&from).cloned().unwrap_or_default();
+    while let Some(address) = pending.pop() {
+        if address == target {
+            return true;
+        }
+        if seen.insert(address) {
+            pending.extend(successors.get(&address).into_iter().flatten());
+        }
+    }
+    false
+}
+
+// Rust expression for the address a parameter refers to
+fn address(operand: &Operand, pointer: usize, ip: usize) -> String {
+    let word = format!("load(memory, {})?", pointer);
+    match operand.mode {
+        Mode::Position => word,
+        Mode::Relative => format!("offset(relative_base, {}, {})?", word, ip),
+        Mode::Immediate => unreachable!("decoded instructions never write to immediates"),
+    }
+}
+
+// Rust expression for the value of a parameter
+fn argument(operand: &Operand, pointer: usize, ip: usize) -> String {
+    match operand.mode {
+        Mode::Immediate => format!("load(memory, {})?", pointer),
+        _ => format!("load(memory, {})?", address(operand, pointer, ip)),
+    }
+}
+
+// Writes the match arm for the instruction at `ip`
+fn compile_instruction(code: &mut String, ip: usize, instruction: &Instruction) -> fmt::Result {
+    let operands = &instruction.operands;
+    let arg = |index: usize| argument(&operands[index], ip + index + 1, ip);
+    let addr = |index: usize| address(&operands[index], ip + index + 1, ip);
+    let next = ip + instruction.size();
+    let overflow = format!(
+        "Stop::Fault(format!(\"arithmetic overflow at address {{}}\", {}))",
+        ip
+    );
+
+    writeln!(code, "                // {}", instruction)?;
+    writeln!(code, "                {} => {{", ip)?;
+    writeln!(
+        code,
+        "                    check(memory, {}, {})?;",
+        ip,
+        instruction.encode()[0]
+    )?;
+
+    let body = match instruction.mnemonic {
+        Mnemonic::Add | Mnemonic::Mul | Mnemonic::Lt | Mnemonic::Eq => {
+            let result = match instruction.mnemonic {
+                Mnemonic::Add => format!("a.checked_add(b).ok_or_else(|| {})?", overflow),
+                Mnemonic::Mul => format!("a.checked_mul(b).ok_or_else(|| {})?", overflow),
+                Mnemonic::Lt => String::from("(a < b) as isize"),
+                _ => String::from("(a == b) as isize"),
+            };
+            vec![
+                format!("let a = {};", arg(0)),
+                format!("let b = {};", arg(1)),
+                format!("let target = {};", addr(2)),
+                format!("store(memory, target, {})?;", result),
+                format!("ip = {};", next),
+            ]
+        }
+        Mnemonic::In => vec![
+            format!("let target = {};", addr(0)),
+            String::from("let value = input_fn()"),
+            String::from("    .ok_or_else(|| Stop::Fault(String::from(\"input exhausted\")))?;"),
+            String::from("store(memory, target, value)?;"),
+            format!("ip = {};", next),
+        ],
+        Mnemonic::Out => vec![format!("output_fn({});", arg(0)), format!("ip = {};", next)],
+        Mnemonic::Jnz | Mnemonic::Jz => {
+            let condition = match instruction.mnemonic {
+                Mnemonic::Jnz => "!=",
+                _ => "==",
+            };
+            vec![
+                format!("if {} {} 0 {{", arg(0), condition),
+                format!("    ip = jump({})?;", arg(1)),
+                String::from("} else {"),
+                format!("    ip = {};", next),
+                String::from("}"),
+            ]
+        }
+        Mnemonic::Arb => vec![
+            format!(
+                "relative_base = offset(relative_base, {}, {})?;",
+                arg(0),
+                ip
+            ),
+            format!("ip = {};", next),
+        ],
+        Mnemonic::Hlt => vec![String::from("return Ok(());")],
+    };
+    for line in body {
+        writeln!(code, "                    {}", line)?;
+    }
+
+    writeln!(code, "                }}")
+}
+
+/// Translate `program` into the source of a Rust module called `name`
+/// ### Examples
+/// ```
+/// use intcode_computer::compiler::{compile, CompileError};
+///
+/// let source = compile(&[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50], "example").unwrap();
+/// assert!(source.contains("pub mod example {"));
+/// assert!(source.contains("// MUL [3], [11], [0]"));
+///
+/// // Overwrites the instruction after it with a halt
+/// assert_eq!(
+///     Err(CompileError::SelfModifying { writer: 0, target: 4 }),
+///     compile(&[1101, 100, -1, 4, 0], "patched")
+/// );
+/// ```
+pub fn compile(program: &[isize], name: &str) -> Result<String, CompileError> {
+    let is_identifier = name
+        .chars()
+        .enumerate()
+        .all(|(index, c)| c == '_' || c.is_ascii_alphabetic() || (index > 0 && c.is_ascii_digit()));
+    if name.is_empty() || !is_identifier {
+        return Err(CompileError::BadName(name.to_string()));
+    }
+
+    let (instructions, successors) = reachable(program);
+
+    // Refuse programs that are known to overwrite an instruction before it is executed.
+    // Writes through a pointer, or ahead of a jump to a computed address, are caught when the
+    // program runs instead.
+    for (&writer, instruction) in &instructions {
+        let writes = instruction.mnemonic.writes();
+        for (operand, _) in instruction
+            .operands
+            .iter()
+            .zip(writes)
+            .filter(|(_, &writes)| writes)
+        {
+            if operand.mode == Mode::Position && operand.value >= 0 {
+                let target = operand.value as usize;
+                if successors.contains_key(&target) && reaches(&successors, writer, target) {
+                    return Err(CompileError::SelfModifying { writer, target });
+                }
+            }
+        }
+    }
+
+    let mut code = String::new();
+    // Writing to a `String` can not fail
+    (|| -> fmt::Result {
+        writeln!(
+            code,
+            "// Generated by intcode_computer::compiler, do not edit"
+        )?;
+        writeln!(
+            code,
+            "#[allow(clippy::all, dead_code, unused_mut, unused_variables)]"
+        )?;
+        writeln!(code, "pub mod {} {{", name)?;
+        code.push_str(PRELUDE);
+        writeln!(code)?;
+
+        writeln!(code, "    /// The program as compiled")?;
+        writeln!(code, "    pub const IMAGE: [isize; {}] = [", program.len())?;
+        for line in program.chunks(WORDS_PER_LINE) {
+            let words = line.iter().map(|word| word.to_string()).collect::<Vec<_>>();
+            writeln!(code, "        {},", words.join(", "))?;
+        }
+        writeln!(code, "    ];")?;
+        writeln!(code)?;
+
+        writeln!(
+            code,
+            "    /// Run the program on `memory`, usually made from `IMAGE` with `Memory::new`"
+        )?;
+        writeln!(code, "    pub fn run<F, G>(")?;
+        writeln!(code, "        memory: &mut Memory,")?;
+        writeln!(code, "        input_fn: &mut F,")?;
+        writeln!(code, "        output_fn: &mut G,")?;
+        writeln!(code, "    ) -> Result<(), Stop>")?;
+        writeln!(code, "    where")?;
+        writeln!(code, "        F: FnMut() -> Option<isize>,")?;
+        writeln!(code, "        G: FnMut(isize),")?;
+        writeln!(code, "    {{")?;
+        writeln!(code, "        let mut ip: usize = 0;")?;
+        writeln!(code, "        let mut relative_base: isize = 0;")?;
+        writeln!(code, "        loop {{")?;
+        writeln!(code, "            match ip {{")?;
+        for (&ip, instruction) in &instructions {
+            compile_instruction(&mut code, ip, instruction)?;
+        }
+        writeln!(
+            code,
+            "                _ => return Err(Stop::Unsupported(ip)),"
+        )?;
+        writeln!(code, "            }}")?;
+        writeln!(code, "        }}")?;
+        writeln!(code, "    }}")?;
+        writeln!(code, "}}")
+    })()
+    .expect("writing to a String failed");
+
+    Ok(code)
+}
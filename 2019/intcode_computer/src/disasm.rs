@@ -0,0 +1,102 @@
+//! # Disassembler
+//! Renders an intcode program as a human-readable listing.
+//!
+//! The program is decoded in a single sweep from address 0. Any word that does not begin a
+//! valid instruction is listed as `DATA` and decoding resumes at the following word.
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::instruction::Instruction;
+
+/// ## Entry
+/// A single line of a `Listing`
+#[derive(PartialEq, Debug, Clone)]
+pub struct Entry {
+    /// The address of the first word
+    pub address: usize,
+    /// The raw words covered by the entry
+    pub words: Vec<isize>,
+    /// The decoded instruction, or `None` for a data word
+    pub instruction: Option<Instruction>,
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let raw = self
+            .words
+            .iter()
+            .map(|word| word.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        match &self.instruction {
+            Some(instruction) => write!(f, "{:>6}: {:<24} {}", self.address, raw, instruction),
+            None => write!(f, "{:>6}: {:<24} DATA {}", self.address, raw, self.words[0]),
+        }
+    }
+}
+
+/// ## Listing
+/// A disassembled intcode program
+/// ### Examples
+/// ```
+/// use intcode_computer::disasm::disassemble;
+///
+/// let listing = disassemble(&[1002, 4, 3, 4, 33]).to_string();
+/// let mut lines = listing.lines();
+/// assert_eq!(
+///     Some("     0: 1002 4 3 4               MUL [4], #3, [4]"),
+///     lines.next()
+/// );
+/// assert_eq!(
+///     Some("     4: 33                       DATA 33"),
+///     lines.next()
+/// );
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+pub struct Listing {
+    entries: Vec<Entry>,
+}
+
+impl Listing {
+    /// Get the entries of the listing, in address order
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+impl Display for Listing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        for entry in &self.entries {
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// Disassemble a program, such as the image of a `Machine` loaded with `Machine::from_path`
+/// which is available through `machine.memory().image()`
+pub fn disassemble(program: &[isize]) -> Listing {
+    let mut entries = Vec::new();
+    let mut address = 0;
+
+    while address < program.len() {
+        let entry = match Instruction::decode(program, address) {
+            Some(instruction) => Entry {
+                address,
+                words: program[address..address + instruction.size()].to_vec(),
+                instruction: Some(instruction),
+            },
+            None => Entry {
+                address,
+                words: vec![program[address]],
+                instruction: None,
+            },
+        };
+
+        address += entry.words.len();
+        entries.push(entry);
+    }
+
+    Listing { entries }
+}
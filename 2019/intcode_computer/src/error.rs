@@ -0,0 +1,80 @@
+//! # Error
+//! Faults that can occur while executing an intcode program.
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::memory::MemoryError;
+
+/// ## IntcodeError
+/// The reasons an intcode program can fail to execute
+#[derive(PartialEq, Debug, Clone)]
+pub enum IntcodeError {
+    /// The instruction at `address` has an opcode the computer does not understand. Instructions
+    /// that do not fit in an `isize` are clamped.
+    UnknownOpcode { opcode: isize, address: usize },
+    /// The instruction at `address` uses a parameter mode the computer does not understand,
+    /// or uses immediate mode for a parameter that is written to
+    BadParameterMode { mode: usize, address: usize },
+    /// The instruction at `address` tried to access or jump to a negative address. Targets that
+    /// do not fit in an `isize` are clamped.
+    NegativeAddress { target: isize, address: usize },
+    /// An access was attempted beyond the memory limit of the computer
+    OutOfBounds { address: usize, limit: usize },
+    /// The result of the instruction at `address` does not fit in the word type of the machine
+    Overflow { address: usize },
+    /// The program asked for input but none was available
+    InputExhausted,
+    /// The computer was asked to execute after it had stopped running
+    NotRunning,
+    /// The computer ran out of steps or time; see `Machine::with_step_budget`
+    BudgetExhausted,
+    /// The computer returned to the state it was in `period` steps earlier, at `address`,
+    /// without consuming input, so would never stop
+    InfiniteLoop { address: usize, period: usize },
+}
+
+impl Display for IntcodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            IntcodeError::UnknownOpcode { opcode, address } => {
+                write!(f, "unknown opcode {} at address {}", opcode, address)
+            }
+            IntcodeError::BadParameterMode { mode, address } => {
+                write!(f, "bad parameter mode {} at address {}", mode, address)
+            }
+            IntcodeError::NegativeAddress { target, address } => write!(
+                f,
+                "negative address {} used by the instruction at address {}",
+                target, address
+            ),
+            IntcodeError::OutOfBounds { address, limit } => write!(
+                f,
+                "address {} is beyond the memory limit of {}",
+                address, limit
+            ),
+            IntcodeError::Overflow { address } => {
+                write!(f, "arithmetic overflow at address {}", address)
+            }
+            IntcodeError::InputExhausted => write!(f, "input exhausted"),
+            IntcodeError::NotRunning => write!(f, "the machine is not running"),
+            IntcodeError::BudgetExhausted => write!(f, "the machine exhausted its budget"),
+            IntcodeError::InfiniteLoop { address, period } => write!(
+                f,
+                "infinite loop of {} steps at address {}",
+                period, address
+            ),
+        }
+    }
+}
+
+impl Error for IntcodeError {}
+
+impl From<MemoryError> for IntcodeError {
+    fn from(error: MemoryError) -> Self {
+        IntcodeError::OutOfBounds {
+            address: error.address,
+            limit: error.limit,
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! # Fast
+//! An execution engine for heavy workloads that decodes each instruction once.
+//!
+//! `FastMachine` wraps a `Machine` and keeps a cache of decoded instructions alongside its
+//! memory, so the opcode and parameter modes are only worked out the first time an address is
+//! executed. Writes into memory invalidate any cached instruction they overlap, so
+//! self-modifying programs behave exactly as they do on the interpreter. Instructions the
+//! decoder does not accept, and every instruction of a traced machine or one recording self
+//! modification, are run by the interpreter itself.
+use std::mem;
+
+use crate::instruction::{Instruction, Mnemonic, Mode};
+use crate::snapshot::Snapshot;
+use crate::{Event, ExecutionState, IntcodeError, Machine};
+
+// The largest number of words an instruction occupies
+const MAX_SIZE: usize = 4;
+
+// A compact, allocation free form of `Instruction`
+#[derive(Debug, Clone, Copy)]
+struct Decoded {
+    mnemonic: Mnemonic,
+    params: [(Mode, isize); MAX_SIZE - 1],
+}
+
+impl Decoded {
+    fn new(instruction: &Instruction) -> Decoded {
+        let mut params = [(Mode::Immediate, 0); MAX_SIZE - 1];
+        for (param, operand) in params.iter_mut().zip(&instruction.operands) {
+            *param = (operand.mode, operand.value);
+        }
+
+        Decoded {
+            mnemonic: instruction.mnemonic,
+            params,
+        }
+    }
+}
+
+/// ## FastMachine
+/// An intcode computer that caches decoded instructions
+/// ### Examples
+/// ```
+/// use intcode_computer::fast::FastMachine;
+/// use intcode_computer::Machine;
+///
+/// // Count down from 3 to 0, outputting each value
+/// let machine = Machine::new(vec![4, 11, 1001, 11, -1, 11, 1005, 11, 0, 99, 0, 3]);
+/// let mut machine = FastMachine::new(machine);
+/// let mut outputs = Vec::new();
+/// machine.execute(&mut || None, &mut |out| outputs.push(out)).unwrap();
+/// assert_eq!(vec![3, 2, 1], outputs);
+/// ```
+///
+/// Programs that overwrite their own instructions see the new instructions
+/// ```
+/// use intcode_computer::fast::FastMachine;
+/// use intcode_computer::Machine;
+///
+/// // The first pass doubles the value at address 16 and turns the add into a multiply, so the
+/// // second pass squares it
+/// let program = vec![1, 16, 16, 16, 1001, 0, 1, 0, 1008, 0, 3, 17, 1006, 17, 0, 99, 3, 0];
+/// let mut machine = FastMachine::new(Machine::new(program));
+/// machine.execute(&mut || None, &mut |_out| {}).unwrap();
+/// assert_eq!(36, machine.machine().memory().get(16).unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FastMachine {
+    machine: Machine,
+    cache: Vec<Option<Decoded>>,
+}
+
+impl FastMachine {
+    /// Create a new `FastMachine` continuing from the current state of `machine`
+    pub fn new(machine: Machine) -> FastMachine {
+        let cache = vec![None; machine.memory.image().len()];
+        FastMachine { machine, cache }
+    }
+
+    /// Get the underlying `Machine`
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+
+    /// Get mutable access to the underlying `Machine`. Any cached instructions are discarded,
+    /// as its memory may be changed.
+    pub fn machine_mut(&mut self) -> &mut Machine {
+        self.clear_cache();
+        &mut self.machine
+    }
+
+    /// Get the underlying `Machine` back
+    pub fn into_machine(self) -> Machine {
+        self.machine
+    }
+
+    /// Set the noun and verb in the program memory (positions 1 and 2)
+    pub fn set_noun_verb(&mut self, noun: isize, verb: isize) -> Result<(), IntcodeError> {
+        self.machine.set_noun_verb(noun, verb)?;
+        self.invalidate(1);
+        self.invalidate(2);
+        Ok(())
+    }
+
+    /// Return the machine to the state captured in `snapshot`
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.machine.restore(snapshot);
+        self.clear_cache();
+    }
+
+    /// Push a value onto the input queue consumed by `FastMachine::run_until_event`
+    pub fn push_input(&mut self, value: isize) {
+        self.machine.push_input(value);
+    }
+
+    /// Execute a single instruction. Behaves exactly as `Machine::step`.
+    pub fn step<F, G>(&mut self, input_fn: &mut F, output_fn: &mut G) -> Result<(), IntcodeError>
+    where
+        F: FnMut() -> Option<isize>,
+        G: FnMut(isize),
+    {
+        if self.machine.exec_state != ExecutionState::Running {
+            return Err(IntcodeError::NotRunning);
+        }
+
+        match self.try_step(input_fn) {
+            Ok(Some(Event::NeedsInput)) => Err(self.fault(IntcodeError::InputExhausted)),
+            Ok(Some(Event::Output(out))) => {
+                output_fn(out);
+                Ok(())
+            }
+            Ok(_) => Ok(()),
+            Err(why) => Err(self.fault(why)),
+        }
+    }
+
+    /// Execute the program in memory until it halts or faults. Behaves exactly as
+    /// `Machine::execute`.
+    pub fn execute<F, G>(&mut self, input_fn: &mut F, output_fn: &mut G) -> Result<(), IntcodeError>
+    where
+        F: FnMut() -> Option<isize>,
+        G: FnMut(isize),
+    {
+        if self.machine.exec_state != ExecutionState::Running {
+            return Err(IntcodeError::NotRunning);
+        }
+
+        loop {
+            match self.try_step(input_fn) {
+                Ok(Some(Event::Halted)) => return Ok(()),
+                Ok(Some(Event::Output(out))) => output_fn(out),
+                Ok(Some(Event::NeedsInput)) => {
+                    return Err(self.fault(IntcodeError::InputExhausted));
+                }
+                Ok(None) => continue,
+                Err(why) => return Err(self.fault(why)),
+            }
+        }
+    }
+
+    /// Execute the program in memory until it produces output, needs input that has not been
+    /// pushed or reaches the end. Behaves exactly as `Machine::run_until_event`.
+    pub fn run_until_event(&mut self) -> Result<Event, IntcodeError> {
+        if self.machine.exec_state != ExecutionState::Running {
+            return Err(IntcodeError::NotRunning);
+        }
+
+        let mut inputs = mem::take(&mut self.machine.inputs);
+        let result = loop {
+            match self.try_step(&mut || inputs.pop_front()) {
+                Ok(Some(event)) => break Ok(event),
+                Ok(None) => continue,
+                Err(why) => break Err(self.fault(why)),
+            }
+        };
+        self.machine.inputs = inputs;
+
+        result
+    }
+
+    /// Get the result of the program (memory location 0)
+    pub fn result(&self) -> isize {
+        self.machine.result()
+    }
+
+    fn fault(&mut self, why: IntcodeError) -> IntcodeError {
+        self.machine.stop(&why);
+        why
+    }
+
+    fn clear_cache(&mut self) {
+        self.cache = vec![None; self.machine.memory.image().len()];
+    }
+
+    // Discards any cached instruction that includes `address`
+    fn invalidate(&mut self, address: usize) {
+        let start = address.saturating_sub(MAX_SIZE - 1);
+        let end = (address + 1).min(self.cache.len());
+        for entry in self.cache.iter_mut().take(end).skip(start) {
+            *entry = None;
+        }
+    }
+
+    fn decode(&mut self) -> Option<Decoded> {
+        let ip = self.machine.inst_pointer;
+        if let Some(Some(decoded)) = self.cache.get(ip) {
+            return Some(*decoded);
+        }
+
+        let words = (ip..ip + MAX_SIZE)
+            .map_while(|address| self.machine.memory.get(address).ok())
+            .collect::<Vec<_>>();
+        let decoded = Decoded::new(&Instruction::decode(&words, 0)?);
+        if let Some(entry) = self.cache.get_mut(ip) {
+            *entry = Some(decoded);
+        }
+
+        Some(decoded)
+    }
+
+    // Executes a single instruction, returning any event it caused, in the same way as
+    // `Machine::try_step`
+    fn try_step<F>(&mut self, input_fn: &mut F) -> Result<Option<Event>, IntcodeError>
+    where
+        F: FnMut() -> Option<isize>,
+    {
+        let observed = self.machine.tracer.is_some()
+            || self.machine.modification.is_some()
+            || self.machine.limits.is_set();
+        let decoded = if observed { None } else { self.decode() };
+        let decoded = match decoded {
+            Some(decoded) => decoded,
+            None => return self.interpret(input_fn),
+        };
+
+        let ip = self.machine.inst_pointer;
+        let overflow = IntcodeError::Overflow { address: ip };
+        let [p0, p1, p2] = decoded.params;
+
+        match decoded.mnemonic {
+            Mnemonic::Add | Mnemonic::Mul | Mnemonic::Lt | Mnemonic::Eq => {
+                let arg0 = self.read(p0)?;
+                let arg1 = self.read(p1)?;
+                let output_addr = self.address(p2)?;
+
+                let result = match decoded.mnemonic {
+                    Mnemonic::Add => arg0.checked_add(arg1).ok_or(overflow)?,
+                    Mnemonic::Mul => arg0.checked_mul(arg1).ok_or(overflow)?,
+                    Mnemonic::Lt => (arg0 < arg1) as isize,
+                    Mnemonic::Eq => (arg0 == arg1) as isize,
+                    _ => unreachable!(),
+                };
+                self.write(output_addr, result)?;
+
+                self.machine.inst_pointer += 4;
+            }
+            Mnemonic::In => {
+                let target_addr = self.address(p0)?;
+                match input_fn() {
+                    Some(input) => self.write(target_addr, input)?,
+                    None => return Ok(Some(Event::NeedsInput)),
+                }
+
+                self.machine.inst_pointer += 2;
+            }
+            Mnemonic::Out => {
+                let out = self.read(p0)?;
+                self.machine.inst_pointer += 2;
+
+                return Ok(Some(Event::Output(out)));
+            }
+            Mnemonic::Jnz | Mnemonic::Jz => {
+                let is_zero = self.read(p0)? == 0;
+                if is_zero == (decoded.mnemonic == Mnemonic::Jz) {
+                    let target = self.read(p1)?;
+                    self.machine.inst_pointer = self.to_address(target)?;
+                } else {
+                    self.machine.inst_pointer += 3;
+                }
+            }
+            Mnemonic::Arb => {
+                let adjustment = self.read(p0)?;
+                self.machine.relative_base = self
+                    .machine
+                    .relative_base
+                    .checked_add(adjustment)
+                    .ok_or(overflow)?;
+
+                self.machine.inst_pointer += 2;
+            }
+            Mnemonic::Hlt => {
+                self.machine.exec_state = ExecutionState::Halted;
+                self.machine.inst_pointer += 1;
+
+                return Ok(Some(Event::Halted));
+            }
+        }
+
+        Ok(None)
+    }
+
+    // Runs the next instruction on the interpreter
+    fn interpret<F>(&mut self, input_fn: &mut F) -> Result<Option<Event>, IntcodeError>
+    where
+        F: FnMut() -> Option<isize>,
+    {
+        let opcode = self
+            .machine
+            .memory
+            .get(self.machine.inst_pointer)
+            .map(|word| word % 100);
+        let result = self.machine.limited_step(input_fn);
+
+        // The interpreter does not say where it wrote, so be conservative; only the standard
+        // instructions below are known not to write
+        if !matches!(opcode, Ok(4) | Ok(5) | Ok(6) | Ok(9) | Ok(99)) {
+            self.clear_cache();
+        }
+
+        result
+    }
+
+    fn to_address(&self, target: isize) -> Result<usize, IntcodeError> {
+        self.machine.to_address(&target)
+    }
+
+    fn address(&self, (mode, value): (Mode, isize)) -> Result<usize, IntcodeError> {
+        match mode {
+            Mode::Position => self.to_address(value),
+            Mode::Relative => match self.machine.relative_base.checked_add(value) {
+                Some(target) => self.to_address(target),
+                None => Err(IntcodeError::Overflow {
+                    address: self.machine.inst_pointer,
+                }),
+            },
+            Mode::Immediate => unreachable!("decoded instructions never write to immediates"),
+        }
+    }
+
+    fn read(&self, param: (Mode, isize)) -> Result<isize, IntcodeError> {
+        match param.0 {
+            Mode::Immediate => Ok(param.1),
+            _ => Ok(self.machine.memory.get(self.address(param)?)?),
+        }
+    }
+
+    fn write(&mut self, address: usize, value: isize) -> Result<(), IntcodeError> {
+        self.machine.memory.set(address, value)?;
+        self.invalidate(address);
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+//! # Instruction
+//! Symbolic representation of intcode instructions, shared by the disassembler and assembler.
+//!
+//! Operands are written as `[12]` in position mode, `#12` in immediate mode and `[rb+12]` in
+//! relative mode.
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// ## Mnemonic
+/// The instructions understood by the intcode computer
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Mnemonic {
+    /// Opcode 1; add the first two parameters and store the sum in the third
+    Add,
+    /// Opcode 2; multiply the first two parameters and store the product in the third
+    Mul,
+    /// Opcode 3; store a value read from the input in the parameter
+    In,
+    /// Opcode 4; output the value of the parameter
+    Out,
+    /// Opcode 5; jump to the second parameter if the first is not zero
+    Jnz,
+    /// Opcode 6; jump to the second parameter if the first is zero
+    Jz,
+    /// Opcode 7; store 1 in the third parameter if the first is less than the second, else 0
+    Lt,
+    /// Opcode 8; store 1 in the third parameter if the first two are equal, else 0
+    Eq,
+    /// Opcode 9; adjust the relative base by the parameter
+    Arb,
+    /// Opcode 99; halt
+    Hlt,
+}
+
+/// Every `Mnemonic`, in opcode order
+pub const MNEMONICS: [Mnemonic; 10] = [
+    Mnemonic::Add,
+    Mnemonic::Mul,
+    Mnemonic::In,
+    Mnemonic::Out,
+    Mnemonic::Jnz,
+    Mnemonic::Jz,
+    Mnemonic::Lt,
+    Mnemonic::Eq,
+    Mnemonic::Arb,
+    Mnemonic::Hlt,
+];
+
+impl Mnemonic {
+    /// Get the `Mnemonic` for an opcode
+    pub fn from_opcode(opcode: isize) -> Option<Mnemonic> {
+        MNEMONICS
+            .iter()
+            .copied()
+            .find(|mnemonic| mnemonic.opcode() == opcode)
+    }
+
+    /// Get the `Mnemonic` with the given name, ignoring case
+    pub fn from_name(name: &str) -> Option<Mnemonic> {
+        MNEMONICS
+            .iter()
+            .copied()
+            .find(|mnemonic| mnemonic.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Get the opcode of the instruction
+    pub fn opcode(self) -> isize {
+        match self {
+            Mnemonic::Add => 1,
+            Mnemonic::Mul => 2,
+            Mnemonic::In => 3,
+            Mnemonic::Out => 4,
+            Mnemonic::Jnz => 5,
+            Mnemonic::Jz => 6,
+            Mnemonic::Lt => 7,
+            Mnemonic::Eq => 8,
+            Mnemonic::Arb => 9,
+            Mnemonic::Hlt => 99,
+        }
+    }
+
+    /// Get the upper case name of the instruction
+    pub fn name(self) -> &'static str {
+        match self {
+            Mnemonic::Add => "ADD",
+            Mnemonic::Mul => "MUL",
+            Mnemonic::In => "IN",
+            Mnemonic::Out => "OUT",
+            Mnemonic::Jnz => "JNZ",
+            Mnemonic::Jz => "JZ",
+            Mnemonic::Lt => "LT",
+            Mnemonic::Eq => "EQ",
+            Mnemonic::Arb => "ARB",
+            Mnemonic::Hlt => "HLT",
+        }
+    }
+
+    /// Get whether each parameter of the instruction is written to
+    pub fn writes(self) -> &'static [bool] {
+        match self {
+            Mnemonic::Add | Mnemonic::Mul | Mnemonic::Lt | Mnemonic::Eq => &[false, false, true],
+            Mnemonic::In => &[true],
+            Mnemonic::Out | Mnemonic::Arb => &[false],
+            Mnemonic::Jnz | Mnemonic::Jz => &[false, false],
+            Mnemonic::Hlt => &[],
+        }
+    }
+
+    /// Get the number of parameters the instruction takes
+    pub fn arity(self) -> usize {
+        self.writes().len()
+    }
+}
+
+impl Display for Mnemonic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// ## Mode
+/// The ways a parameter can be interpreted
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Mode {
+    /// The parameter is the address of the value
+    Position,
+    /// The parameter is the value
+    Immediate,
+    /// The parameter is the address of the value, offset by the relative base
+    Relative,
+}
+
+impl Mode {
+    /// Get the `Mode` for a parameter mode digit
+    pub fn from_digit(digit: isize) -> Option<Mode> {
+        match digit {
+            0 => Some(Mode::Position),
+            1 => Some(Mode::Immediate),
+            2 => Some(Mode::Relative),
+            _ => None,
+        }
+    }
+
+    /// Get the parameter mode digit
+    pub fn digit(self) -> isize {
+        match self {
+            Mode::Position => 0,
+            Mode::Immediate => 1,
+            Mode::Relative => 2,
+        }
+    }
+}
+
+/// ## Operand
+/// A parameter of an instruction along with its mode
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Operand {
+    pub mode: Mode,
+    pub value: isize,
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self.mode {
+            Mode::Position => write!(f, "[{}]", self.value),
+            Mode::Immediate => write!(f, "#{}", self.value),
+            Mode::Relative if self.value < 0 => write!(f, "[rb{}]", self.value),
+            Mode::Relative => write!(f, "[rb+{}]", self.value),
+        }
+    }
+}
+
+/// ## Instruction
+/// A decoded intcode instruction
+/// ### Examples
+/// ```
+/// use intcode_computer::instruction::{Instruction, Mnemonic};
+///
+/// let instruction = Instruction::decode(&[1002, 4, 3, 4], 0).unwrap();
+/// assert_eq!(Mnemonic::Mul, instruction.mnemonic);
+/// assert_eq!("MUL [4], #3, [4]", instruction.to_string());
+/// assert_eq!(vec![1002, 4, 3, 4], instruction.encode());
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+pub struct Instruction {
+    pub mnemonic: Mnemonic,
+    pub operands: Vec<Operand>,
+}
+
+impl Instruction {
+    /// Decode the instruction at `address` in `program`. Returns `None` if the words there are
+    /// not a valid instruction.
+    pub fn decode(program: &[isize], address: usize) -> Option<Instruction> {
+        let word = *program.get(address)?;
+        if word < 0 {
+            return None;
+        }
+
+        let mnemonic = Mnemonic::from_opcode(word % 100)?;
+        let mut modes = word / 100;
+        let operands = mnemonic
+            .writes()
+            .iter()
+            .enumerate()
+            .map(|(arg_number, &writes)| {
+                let mode = Mode::from_digit(modes % 10)?;
+                modes /= 10;
+                if writes && mode == Mode::Immediate {
+                    return None;
+                }
+
+                Some(Operand {
+                    mode,
+                    value: *program.get(address + arg_number + 1)?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        if modes != 0 {
+            return None;
+        }
+
+        Some(Instruction { mnemonic, operands })
+    }
+
+    /// Encode the instruction as the words that represent it in memory
+    pub fn encode(&self) -> Vec<isize> {
+        let modes = self
+            .operands
+            .iter()
+            .rev()
+            .fold(0, |modes, operand| modes * 10 + operand.mode.digit());
+
+        let mut words = vec![modes * 100 + self.mnemonic.opcode()];
+        words.extend(self.operands.iter().map(|operand| operand.value));
+        words
+    }
+
+    /// Get the number of words the instruction occupies in memory
+    pub fn size(&self) -> usize {
+        self.operands.len() + 1
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.mnemonic)?;
+        for (index, operand) in self.operands.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{}{}", separator, operand)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+//! # Instruction Set
+//! Extending the intcode computer with opcodes of your own.
+//!
+//! An `InstructionSet` is the standard 2019 instruction set along with any `Operation`s
+//! registered with it. Each operation declares which of its parameters are written to; the
+//! machine resolves the parameter modes in the same way as for the standard instructions, so an
+//! operation sees the values of its parameters and can store results, output, jump or halt
+//! through its `Operands`.
+//!
+//! The standard opcodes cannot be replaced. The instruction set is not part of a `Snapshot`, so
+//! a machine restored from one uses the standard set until it is given another.
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+use crate::instruction::Mnemonic;
+use crate::{Event, ExecutionState, IntcodeError, Machine, Word};
+
+/// ## RegisterError
+/// The reasons an `Operation` cannot be registered
+#[derive(PartialEq, Debug, Clone)]
+pub enum RegisterError {
+    /// The opcode belongs to an instruction of the standard set
+    Standard(isize),
+    /// The opcode does not fit in the last two digits of an instruction
+    OutOfRange(isize),
+    /// Another operation is already registered with the opcode
+    Taken(isize),
+}
+
+impl Display for RegisterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            RegisterError::Standard(opcode) => {
+                write!(f, "opcode {} is a standard instruction", opcode)
+            }
+            RegisterError::OutOfRange(opcode) => {
+                write!(f, "opcode {} is not between 0 and 99", opcode)
+            }
+            RegisterError::Taken(opcode) => write!(f, "opcode {} is already registered", opcode),
+        }
+    }
+}
+
+impl Error for RegisterError {}
+
+/// ## Operation
+/// The behaviour of an instruction added to an `InstructionSet`
+pub trait Operation<W = isize>: Send + Sync {
+    /// Get the upper case name of the instruction
+    fn name(&self) -> &str;
+
+    /// Get whether each parameter of the instruction is written to
+    fn writes(&self) -> &[bool];
+
+    /// Execute the instruction. Returning an error faults the machine.
+    fn execute(&self, operands: &mut Operands<W>) -> Result<(), IntcodeError>;
+}
+
+/// ## Operands
+/// The parameters of an `Operation` being executed, and what it does with them
+pub struct Operands<W = isize> {
+    address: usize,
+    relative_base: isize,
+    values: Vec<W>,
+    // The address each parameter that is written to refers to
+    targets: Vec<Option<usize>>,
+    writes: Vec<(usize, W)>,
+    output: Option<W>,
+    jump: Option<W>,
+    halt: bool,
+}
+
+impl<W: Word> Operands<W> {
+    /// Get the address of the instruction
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Get the relative base of the machine
+    pub fn relative_base(&self) -> isize {
+        self.relative_base
+    }
+
+    /// Get the number of parameters
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Get whether the instruction has no parameters
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Get the value of a parameter. For a parameter that is written to, this is the value
+    /// stored at the address it refers to before the instruction executed.
+    pub fn get(&self, index: usize) -> &W {
+        &self.values[index]
+    }
+
+    /// Store `value` at the address a parameter refers to once the instruction completes
+    ///
+    /// Panics if the operation did not declare that the parameter is written to.
+    pub fn set(&mut self, index: usize, value: W) {
+        let target = self.targets[index].expect("parameter is not written to");
+        self.writes.push((target, value));
+    }
+
+    /// Output a value
+    pub fn output(&mut self, value: W) {
+        self.output = Some(value);
+    }
+
+    /// Continue at `target` instead of the next instruction
+    pub fn jump(&mut self, target: W) {
+        self.jump = Some(target);
+    }
+
+    /// Halt the machine once the instruction completes, instead of producing any output
+    pub fn halt(&mut self) {
+        self.halt = true;
+    }
+}
+
+/// ## InstructionSet
+/// The instructions a machine understands; the standard set and any registered operations
+/// ### Examples
+/// ```
+/// use intcode_computer::isa::{InstructionSet, Operands, Operation};
+/// use intcode_computer::{IntcodeError, Machine};
+///
+/// // Opcode 10 stores the remainder of dividing the first parameter by the second
+/// struct Rem;
+///
+/// impl Operation for Rem {
+///     fn name(&self) -> &str {
+///         "REM"
+///     }
+///
+///     fn writes(&self) -> &[bool] {
+///         &[false, false, true]
+///     }
+///
+///     fn execute(&self, operands: &mut Operands) -> Result<(), IntcodeError> {
+///         let overflow = IntcodeError::Overflow {
+///             address: operands.address(),
+///         };
+///         let remainder = operands.get(0).checked_rem(*operands.get(1)).ok_or(overflow)?;
+///         operands.set(2, remainder);
+///         Ok(())
+///     }
+/// }
+///
+/// let mut instructions = InstructionSet::standard();
+/// instructions.register(10, Rem).unwrap();
+/// assert_eq!(Some("REM"), instructions.name(10));
+/// assert_eq!(Some("MUL"), instructions.name(2));
+///
+/// let mut machine = Machine::new(vec![1110, 47, 10, 7, 4, 7, 99, 0])
+///     .with_instruction_set(instructions);
+/// let mut outputs = Vec::new();
+/// machine.execute(&mut || None, &mut |out| outputs.push(out)).unwrap();
+/// assert_eq!(vec![7], outputs);
+/// ```
+#[derive(Clone)]
+pub struct InstructionSet<W = isize> {
+    operations: BTreeMap<isize, Arc<dyn Operation<W>>>,
+}
+
+impl<W> Default for InstructionSet<W> {
+    fn default() -> Self {
+        InstructionSet {
+            operations: BTreeMap::new(),
+        }
+    }
+}
+
+impl<W: Word> InstructionSet<W> {
+    /// Create an `InstructionSet` holding only the standard instructions
+    pub fn standard() -> InstructionSet<W> {
+        InstructionSet::default()
+    }
+
+    /// Add an instruction with the given opcode
+    pub fn register<O>(&mut self, opcode: isize, operation: O) -> Result<(), RegisterError>
+    where
+        O: Operation<W> + 'static,
+    {
+        if Mnemonic::from_opcode(opcode).is_some() {
+            return Err(RegisterError::Standard(opcode));
+        }
+        if !(0..100).contains(&opcode) {
+            return Err(RegisterError::OutOfRange(opcode));
+        }
+        if self.operations.contains_key(&opcode) {
+            return Err(RegisterError::Taken(opcode));
+        }
+
+        self.operations.insert(opcode, Arc::new(operation));
+        Ok(())
+    }
+
+    /// Get the operation registered with an opcode
+    pub fn operation(&self, opcode: isize) -> Option<&dyn Operation<W>> {
+        self.operations.get(&opcode).map(|operation| &**operation)
+    }
+
+    /// Get the name of the instruction with an opcode, standard or registered
+    pub fn name(&self, opcode: isize) -> Option<&str> {
+        match Mnemonic::from_opcode(opcode) {
+            Some(mnemonic) => Some(mnemonic.name()),
+            None => self.operation(opcode).map(Operation::name),
+        }
+    }
+
+    /// Get whether each parameter of the instruction with an opcode is written to
+    pub fn writes(&self, opcode: isize) -> Option<&[bool]> {
+        match Mnemonic::from_opcode(opcode) {
+            Some(mnemonic) => Some(mnemonic.writes()),
+            None => self.operation(opcode).map(Operation::writes),
+        }
+    }
+
+    /// Get the opcodes of the registered operations, in order
+    pub fn opcodes(&self) -> Vec<isize> {
+        self.operations.keys().copied().collect()
+    }
+}
+
+impl<W> Debug for InstructionSet<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_map()
+            .entries(
+                self.operations
+                    .iter()
+                    .map(|(opcode, operation)| (opcode, operation.name())),
+            )
+            .finish()
+    }
+}
+
+impl<W: Word> Machine<W> {
+    /// Execute programs using `instructions`, which may add opcodes to the standard set
+    pub fn with_instruction_set(mut self, instructions: InstructionSet<W>) -> Machine<W> {
+        self.instructions = instructions;
+        self
+    }
+
+    /// Get the instructions the machine understands
+    pub fn instruction_set(&self) -> &InstructionSet<W> {
+        &self.instructions
+    }
+
+    // Executes the registered operation with `opcode`, if there is one
+    pub(crate) fn try_operation(
+        &mut self,
+        opcode: isize,
+        access_flags: usize,
+    ) -> Option<Result<Option<Event<W>>, IntcodeError>> {
+        let operation = self.instructions.operations.get(&opcode)?.clone();
+        Some(self.execute_operation(&*operation, access_flags))
+    }
+
+    fn execute_operation(
+        &mut self,
+        operation: &dyn Operation<W>,
+        access_flags: usize,
+    ) -> Result<Option<Event<W>>, IntcodeError> {
+        let writes = operation.writes();
+        let mut values = Vec::with_capacity(writes.len());
+        let mut targets = Vec::with_capacity(writes.len());
+        for (arg_number, &writes) in writes.iter().enumerate() {
+            if writes {
+                let target = self.parse_address(arg_number, access_flags)?;
+                values.push(self.memory.get(target)?);
+                targets.push(Some(target));
+            } else {
+                values.push(self.parse_argument(arg_number, access_flags)?);
+                targets.push(None);
+            }
+        }
+
+        let mut operands = Operands {
+            address: self.inst_pointer,
+            relative_base: self.relative_base,
+            values,
+            targets,
+            writes: Vec::new(),
+            output: None,
+            jump: None,
+            halt: false,
+        };
+        operation.execute(&mut operands)?;
+
+        for (target, value) in operands.writes {
+            self.write(target, value)?;
+        }
+        self.inst_pointer = match &operands.jump {
+            Some(target) => self.to_address(target)?,
+            None => self.inst_pointer + writes.len() + 1,
+        };
+        if operands.halt {
+            self.exec_state = ExecutionState::Halted;
+            return Ok(Some(Event::Halted));
+        }
+
+        Ok(operands.output.map(Event::Output))
+    }
+}
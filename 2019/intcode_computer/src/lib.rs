@@ -2,35 +2,79 @@
 //! Implementation of an intcode computer as defined in the Advent of Code 2019
 //! Used in problems 2, 5 and 9 thus far.
 //!
-use std::error::Error;
+pub mod adapters;
+pub mod asm;
+pub mod compiler;
+pub mod disasm;
+pub mod error;
+pub mod fast;
+pub mod instruction;
+pub mod isa;
+pub mod limits;
+pub mod loader;
+pub mod memory;
+pub mod modification;
+pub mod network;
+pub mod replay;
+pub mod search;
+pub mod snapshot;
+pub mod symbolic;
+pub mod trace;
+pub mod word;
+
+use std::collections::VecDeque;
 use std::fmt;
 use std::fmt::{Display, Formatter};
-use std::fs::File;
-use std::io;
-use std::io::Read;
-use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+pub use error::IntcodeError;
+use isa::InstructionSet;
+use limits::Limits;
+use memory::Memory;
+use modification::SelfModification;
+use trace::{TraceEntry, TraceHook, TraceSink};
+pub use word::Word;
 
 /// ## ExecutionState
 /// The possible states the computer can be in
 /// The computer is running until it reaches op code 99 or hits an error
-/// `ExecutionState::Halted` contains a reason for halting.
+/// `ExecutionState::Faulted` contains the error that stopped the computer.
+/// `ExecutionState::BudgetExhausted` is the only state the computer can resume from.
 #[derive(PartialEq, Debug, Clone)]
 pub enum ExecutionState {
     /// The computer is able to execute
     Running,
-    /// The computer has halted for the reason given
-    Halted(String),
+    /// The computer reached the end of the program
+    Halted,
+    /// The computer stopped due to the error given
+    Faulted(IntcodeError),
+    /// The computer ran out of steps or time before executing its next instruction
+    BudgetExhausted,
 }
 
 impl Display for ExecutionState {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
         match self {
             ExecutionState::Running => write!(f, "running"),
-            ExecutionState::Halted(reason) => write!(f, "halted due to {}", reason),
+            ExecutionState::Halted => write!(f, "halted at end of program"),
+            ExecutionState::Faulted(why) => write!(f, "faulted due to {}", why),
+            ExecutionState::BudgetExhausted => write!(f, "stopped with its budget exhausted"),
         }
     }
 }
 
+/// ## Event
+/// The events that pause a machine driven by `Machine::run_until_event`
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Event<W = isize> {
+    /// The program wants input but none has been pushed
+    NeedsInput,
+    /// The program produced a value
+    Output(W),
+    /// The program reached the end
+    Halted,
+}
+
 /// ## Machine
 /// An intcode computer
 /// ### Examples
@@ -38,70 +82,354 @@ impl Display for ExecutionState {
 /// use intcode_computer::Machine;
 ///
 /// let mut machine = Machine::new(vec![1, 5, 6, 0, 99, 25, 17]);
-/// let mut input_fn = || 0isize;
+/// let mut input_fn = || None;
 /// let mut output_fn = |_out| {};
-/// machine.execute(&mut input_fn, &mut output_fn);
+/// machine.execute(&mut input_fn, &mut output_fn).unwrap();
 /// assert_eq!(42, machine.result());
 /// ```
+///
+/// Parameters in relative mode are offset by the relative base, which opcode 9 adjusts
+/// ```
+/// use intcode_computer::Machine;
+///
+/// let mut machine = Machine::new(vec![109, 4, 204, 3, 99, 0, 0, 42]);
+/// let mut outputs = Vec::new();
+/// machine.execute(&mut || None, &mut |out| outputs.push(out)).unwrap();
+/// assert_eq!(vec![42], outputs);
+/// ```
+///
+/// Memory grows on demand, so programs may use addresses beyond their initial image
+/// ```
+/// use intcode_computer::Machine;
+///
+/// let mut machine = Machine::new(vec![1101, 20, 22, 5000, 4, 5000, 99]);
+/// let mut outputs = Vec::new();
+/// machine.execute(&mut || None, &mut |out| outputs.push(out)).unwrap();
+/// assert_eq!(vec![42], outputs);
+/// ```
+///
+/// Faults are reported as an `IntcodeError` and leave the machine in the `Faulted` state
+/// ```
+/// use intcode_computer::{ExecutionState, IntcodeError, Machine};
+///
+/// let mut machine = Machine::new(vec![3, 0, 99]);
+/// let error = machine.execute(&mut || None, &mut |_out| {}).unwrap_err();
+/// assert_eq!(IntcodeError::InputExhausted, error);
+/// assert_eq!(&ExecutionState::Faulted(error), machine.exec_state());
+/// ```
+///
+/// Machines can pause when they need input, allowing several to be chained in a feedback loop
+/// ```
+/// use intcode_computer::{Event, Machine};
+///
+/// let program = vec![
+///     3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28,
+///     1005, 28, 6, 99, 0, 0, 5,
+/// ];
+/// let mut amplifiers = [9, 8, 7, 6, 5]
+///     .iter()
+///     .map(|&phase| {
+///         let mut amplifier = Machine::new(program.clone());
+///         amplifier.push_input(phase);
+///         amplifier
+///     })
+///     .collect::<Vec<_>>();
+///
+/// let mut signal = 0;
+/// 'feedback: loop {
+///     for amplifier in amplifiers.iter_mut() {
+///         amplifier.push_input(signal);
+///         match amplifier.run_until_event().unwrap() {
+///             Event::Output(out) => signal = out,
+///             Event::Halted => break 'feedback,
+///             Event::NeedsInput => unreachable!(),
+///         }
+///     }
+/// }
+/// assert_eq!(139_629_729, signal);
+/// ```
+///
+/// Arithmetic that overflows the word type faults the machine, so programs that compute large
+/// values can be run with a wider `Word` instead
+/// ```
+/// use intcode_computer::{IntcodeError, Machine};
+///
+/// let program = vec![1102, 1 << 62, 4, 7, 4, 7, 99, 0];
+/// let mut machine = Machine::new(program.clone());
+/// let error = machine.execute(&mut || None, &mut |_out| {}).unwrap_err();
+/// assert_eq!(IntcodeError::Overflow { address: 0 }, error);
+///
+/// let mut machine = Machine::from_words(program.iter().map(|&word| word as i128).collect());
+/// let mut outputs = Vec::new();
+/// machine.execute(&mut || None, &mut |out| outputs.push(out)).unwrap();
+/// assert_eq!(vec![1i128 << 64], outputs);
+/// ```
 #[derive(Debug, Clone)]
-pub struct Machine {
-    memory: Vec<isize>,
+pub struct Machine<W = isize> {
+    memory: Memory<W>,
     exec_state: ExecutionState,
     inst_pointer: usize,
+    relative_base: isize,
+    inputs: VecDeque<W>,
+    tracer: Option<TraceHook<W>>,
+    modification: Option<SelfModification<W>>,
+    limits: Limits<W>,
+    instructions: InstructionSet<W>,
 }
 
 impl Machine {
     /// Create a new `Machine` with a given initial memory vector
     pub fn new(initial_memory: Vec<isize>) -> Machine {
+        Machine::from_words(initial_memory)
+    }
+}
+
+impl<W: Word> Machine<W> {
+    /// Create a new `Machine` holding any type of `Word` with a given initial memory vector
+    pub fn from_words(initial_memory: Vec<W>) -> Machine<W> {
         Machine {
-            memory: initial_memory,
+            memory: Memory::from_words(initial_memory),
             exec_state: ExecutionState::Running,
             inst_pointer: 0,
+            relative_base: 0,
+            inputs: VecDeque::new(),
+            tracer: None,
+            modification: None,
+            limits: Limits::default(),
+            instructions: InstructionSet::standard(),
+        }
+    }
+
+    /// Limit the number of memory cells available to the program. Accessing an address beyond
+    /// the limit faults the machine with `IntcodeError::OutOfBounds`.
+    pub fn with_memory_limit(mut self, limit: usize) -> Machine<W> {
+        self.memory = self.memory.with_limit(limit);
+        self
+    }
+
+    /// Set the noun and verb in the program memory (positions 1 and 2)
+    ///
+    /// Fails with `IntcodeError::OutOfBounds` if the memory limit does not reach both
+    /// positions, in which case memory is left unchanged.
+    /// ### Examples
+    /// ```
+    /// use intcode_computer::{IntcodeError, Machine};
+    ///
+    /// let mut machine = Machine::new(vec![1, 0, 0, 0, 99]);
+    /// machine.set_noun_verb(4, 4).unwrap();
+    /// assert_eq!(&[1, 4, 4, 0, 99], machine.memory().image());
+    ///
+    /// let mut machine = Machine::new(vec![99]).with_memory_limit(2);
+    /// assert_eq!(
+    ///     Err(IntcodeError::OutOfBounds {
+    ///         address: 2,
+    ///         limit: 2
+    ///     }),
+    ///     machine.set_noun_verb(4, 4)
+    /// );
+    /// assert_eq!(&[99], machine.memory().image());
+    /// ```
+    pub fn set_noun_verb(&mut self, noun: isize, verb: isize) -> Result<(), IntcodeError> {
+        self.memory.get(2)?;
+        self.memory.set(1, W::from_isize(noun))?;
+        self.memory.set(2, W::from_isize(verb))?;
+        self.limits.memory_changed();
+        Ok(())
+    }
+
+    /// Execute a single instruction
+    ///
+    /// Any error is also recorded in the `ExecutionState` of the machine, after which it will
+    /// refuse to execute further with `IntcodeError::NotRunning`. Running out of budget is
+    /// reported as `IntcodeError::BudgetExhausted` and leaves the machine able to resume.
+    pub fn step<F, G>(&mut self, input_fn: &mut F, output_fn: &mut G) -> Result<(), IntcodeError>
+    where
+        F: FnMut() -> Option<W>,
+        G: FnMut(W),
+    {
+        if self.exec_state != ExecutionState::Running {
+            return Err(IntcodeError::NotRunning);
+        }
+
+        let result = match self.limited_step(input_fn) {
+            Ok(Some(Event::NeedsInput)) => Err(IntcodeError::InputExhausted),
+            Ok(Some(Event::Output(out))) => {
+                output_fn(out);
+                Ok(())
+            }
+            Ok(_) => Ok(()),
+            Err(why) => Err(why),
+        };
+
+        if let Err(why) = &result {
+            self.stop(why);
         }
+
+        result
     }
 
-    /// Create a new `Machine` using the contents of a file as the initial memory from a
-    /// supplied `&Path`
-    pub fn from_path(path: &Path) -> Result<Machine, io::Error> {
-        let mut file = match File::open(path) {
-            Ok(file) => file,
-            Err(why) => panic!("Couldn't read the input\n{}", why.description()),
+    // Records the reason the machine stopped in its state
+    pub(crate) fn stop(&mut self, why: &IntcodeError) {
+        self.exec_state = match why {
+            IntcodeError::BudgetExhausted => ExecutionState::BudgetExhausted,
+            why => ExecutionState::Faulted(why.clone()),
         };
+    }
+
+    /// Push a value onto the input queue consumed by `Machine::run_until_event`
+    pub fn push_input(&mut self, value: W) {
+        self.inputs.push_back(value);
+    }
+
+    /// Execute the program in memory until it produces output, needs input that has not been
+    /// pushed or reaches the end. Execution can be resumed by calling this method again.
+    pub fn run_until_event(&mut self) -> Result<Event<W>, IntcodeError> {
+        if self.exec_state != ExecutionState::Running {
+            return Err(IntcodeError::NotRunning);
+        }
 
-        let mut prog_string = String::new();
-        file.read_to_string(&mut prog_string)?;
+        let mut inputs = std::mem::take(&mut self.inputs);
+        let result = loop {
+            match self.limited_step(&mut || inputs.pop_front()) {
+                Ok(Some(event)) => break Ok(event),
+                Ok(None) => continue,
+                Err(why) => {
+                    self.stop(&why);
+                    break Err(why);
+                }
+            }
+        };
+        self.inputs = inputs;
 
-        let initial_memory = prog_string
-            .split(',')
-            .filter_map(|op_str| op_str.trim().parse::<isize>().ok())
-            .collect::<Vec<isize>>();
+        result
+    }
 
-        Ok(Machine::new(initial_memory))
+    /// Send a `TraceEntry` to `sink` for every instruction executed from now on. Clones of the
+    /// machine share the same sink.
+    pub fn set_trace_sink(&mut self, sink: Arc<Mutex<dyn TraceSink<W> + Send>>) {
+        self.tracer = Some(TraceHook(sink));
     }
 
-    /// Set the noun and verb in the program memory (positions 1 and 2)
-    pub fn set_noun_verb(&mut self, noun: isize, verb: isize) {
-        self.memory[1] = noun;
-        self.memory[2] = verb;
+    /// Stop sending instructions to the trace sink
+    pub fn clear_trace_sink(&mut self) {
+        self.tracer = None;
     }
 
-    fn step<F, G>(&mut self, input_fn: &mut F, output_fn: &mut G)
+    // Executes a single instruction within the limits set on the machine
+    fn limited_step<F>(&mut self, input_fn: &mut F) -> Result<Option<Event<W>>, IntcodeError>
     where
-        F: FnMut() -> isize,
-        G: FnMut(isize),
+        F: FnMut() -> Option<W>,
     {
-        if self.exec_state != ExecutionState::Running {
-            panic!("Tried to execute a program that had {}!", self.exec_state);
+        if !self.limits.is_set() {
+            return self.traced_step(input_fn);
+        }
+
+        self.limits
+            .before_step(self.inst_pointer, self.relative_base, &self.memory)?;
+        let mut consumed = false;
+        let result = self.traced_step(&mut || {
+            let input = input_fn();
+            consumed |= input.is_some();
+            input
+        });
+        if let Ok(event) = &result {
+            self.limits.after_step(event, consumed);
+        }
+
+        result
+    }
+
+    // Executes a single instruction, recording it with the tracer if there is one
+    fn traced_step<F>(&mut self, input_fn: &mut F) -> Result<Option<Event<W>>, IntcodeError>
+    where
+        F: FnMut() -> Option<W>,
+    {
+        let tracer = match &self.tracer {
+            Some(tracer) => tracer.clone(),
+            None => return self.try_step(input_fn),
+        };
+
+        let entry = self.trace_entry();
+        let result = self.try_step(input_fn);
+
+        if let (Some(mut entry), Ok(event)) = (entry, &result) {
+            if event != &Some(Event::NeedsInput) {
+                if let Some((address, _)) = entry.write {
+                    entry.write = Some((address, self.memory.get(address)?));
+                }
+                tracer.record(&entry);
+            }
+        }
+
+        result
+    }
+
+    // Resolves the operands of the next instruction without executing it
+    fn trace_entry(&self) -> Option<TraceEntry<W>> {
+        let word = self.memory.get(self.inst_pointer).ok()?.to_isize()?;
+        if word < 0 {
+            return None;
         }
+        let opcode = word % 100;
+        let access_flags = (word / 100) as usize;
+
+        let mut write = None;
+        let operands = self
+            .instructions
+            .writes(opcode)?
+            .iter()
+            .enumerate()
+            .map(|(arg_number, &writes)| {
+                if writes {
+                    let address = self.parse_address(arg_number, access_flags).ok()?;
+                    write = Some((address, W::from_isize(0)));
+                    Some(W::from_isize(address as isize))
+                } else {
+                    self.parse_argument(arg_number, access_flags).ok()
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
 
-        let op_code = self.memory[self.inst_pointer] % 100;
-        let access_flags = (self.memory[self.inst_pointer] / 100) as usize;
+        Some(TraceEntry {
+            address: self.inst_pointer,
+            opcode,
+            name: self.instructions.name(opcode)?.to_string(),
+            operands,
+            write,
+        })
+    }
+
+    // Executes a single instruction, returning any event it caused. When input is needed but
+    // `input_fn` has none the instruction pointer is left on the input instruction.
+    fn try_step<F>(&mut self, input_fn: &mut F) -> Result<Option<Event<W>>, IntcodeError>
+    where
+        F: FnMut() -> Option<W>,
+    {
+        let word = self.memory.get(self.inst_pointer)?;
+        let instruction = word.to_isize().ok_or(IntcodeError::UnknownOpcode {
+            opcode: word.saturating_to_isize(),
+            address: self.inst_pointer,
+        })?;
+        let op_code = instruction % 100;
+        let access_flags = (instruction / 100) as usize;
+        if let Some(modification) = &mut self.modification {
+            let size = self
+                .instructions
+                .writes(op_code)
+                .map_or(1, |writes| writes.len() + 1);
+            modification.executed(self.inst_pointer, size);
+        }
+        let overflow = IntcodeError::Overflow {
+            address: self.inst_pointer,
+        };
 
         match op_code {
             // 99 terminates the program; there are no arguments
             99 => {
-                self.exec_state = ExecutionState::Halted(String::from("end of program"));
+                self.exec_state = ExecutionState::Halted;
                 self.inst_pointer += 1;
+
+                return Ok(Some(Event::Halted));
             }
             // 1, 2, 7 and 8 take 3 arguments; two inputs and one output address
             // 1 adds the contents of the two inputs and stores the result at the output address
@@ -109,30 +437,19 @@ impl Machine {
             // 7 compares input 1 to input 2. If input 1 is less than input 2, 1 is stored in the output address else 0 is stored
             // 8 compares input 1 to input 2. If they are equal, 1 is stored in the output address else 0 is stored
             1 | 2 | 7 | 8 => {
-                let arg0 = self.parse_argument(0, access_flags);
-                let arg1 = self.parse_argument(1, access_flags);
-
-                let output_addr = self.memory[self.inst_pointer + 3] as usize;
-
-                self.memory[output_addr] = match op_code {
-                    1 => arg0 + arg1,
-                    2 => arg0 * arg1,
-                    7 => {
-                        if arg0 < arg1 {
-                            1
-                        } else {
-                            0
-                        }
-                    }
-                    8 => {
-                        if arg0 == arg1 {
-                            1
-                        } else {
-                            0
-                        }
-                    }
+                let arg0 = self.parse_argument(0, access_flags)?;
+                let arg1 = self.parse_argument(1, access_flags)?;
+
+                let output_addr = self.parse_address(2, access_flags)?;
+
+                let result = match op_code {
+                    1 => arg0.checked_add(&arg1).ok_or(overflow)?,
+                    2 => arg0.checked_mul(&arg1).ok_or(overflow)?,
+                    7 => W::from_isize(if arg0 < arg1 { 1 } else { 0 }),
+                    8 => W::from_isize(if arg0 == arg1 { 1 } else { 0 }),
                     _ => unreachable!(),
                 };
+                self.write(output_addr, result)?;
 
                 self.inst_pointer += 4;
             }
@@ -140,80 +457,170 @@ impl Machine {
             // 3 waits for user input and stores the input at the address specified
             // 4 outputs to stdout based on the value of its argument
             3 | 4 => {
-                match op_code {
+                let event = match op_code {
                     3 => {
-                        let target_addr = self.memory[self.inst_pointer + 1] as usize;
-                        self.memory[target_addr] = input_fn();
-                    }
-                    4 => {
-                        output_fn(self.parse_argument(0, access_flags));
+                        let target_addr = self.parse_address(0, access_flags)?;
+                        match input_fn() {
+                            Some(input) => self.write(target_addr, input)?,
+                            None => return Ok(Some(Event::NeedsInput)),
+                        }
+                        None
                     }
+                    4 => Some(Event::Output(self.parse_argument(0, access_flags)?)),
                     _ => unreachable!(),
-                }
+                };
 
                 self.inst_pointer += 2;
+
+                return Ok(event);
             }
             // 5 and 6 take 2 arguments; both are inputs
             // 5 checks if the first input is not 0. If this is true, it jumps to the location specified by the second input
             // 6 checks if the first input is 0. If this is true, it jumps to the location specified by the second input
             5 | 6 => {
-                let arg0 = self.parse_argument(0, access_flags);
+                let is_zero = self.parse_argument(0, access_flags)? == W::from_isize(0);
                 let do_jump = match op_code {
-                    5 => arg0 != 0,
-                    6 => arg0 == 0,
+                    5 => !is_zero,
+                    6 => is_zero,
                     _ => unreachable!(),
                 };
 
                 if do_jump {
-                    self.inst_pointer = self.parse_argument(1, access_flags) as usize;
+                    let target = self.parse_argument(1, access_flags)?;
+                    self.inst_pointer = self.to_address(&target)?;
                 } else {
                     self.inst_pointer += 3;
                 }
             }
-            _ => self.exec_state = ExecutionState::Halted(String::from("unknown opcode")),
+            // 9 takes 1 argument; an input
+            // 9 adjusts the relative base by the value of its argument
+            9 => {
+                let adjustment = self.parse_argument(0, access_flags)?;
+                self.relative_base = adjustment
+                    .to_isize()
+                    .and_then(|adjustment| self.relative_base.checked_add(adjustment))
+                    .ok_or(overflow)?;
+                self.inst_pointer += 2;
+            }
+            _ => {
+                if let Some(result) = self.try_operation(op_code, access_flags) {
+                    return result;
+                }
+                return Err(IntcodeError::UnknownOpcode {
+                    opcode: instruction,
+                    address: self.inst_pointer,
+                });
+            }
+        }
+
+        Ok(None)
+    }
+
+    // Writes to memory on behalf of the current instruction
+    fn write(&mut self, address: usize, value: W) -> Result<(), IntcodeError> {
+        if let Some(modification) = &mut self.modification {
+            modification.written(self.inst_pointer, address, &value);
         }
+        self.limits.written(address, &self.memory, &value);
+        Ok(self.memory.set(address, value)?)
+    }
+
+    fn access_mode(arg_number: usize, access_flag: usize) -> usize {
+        access_flag / 10usize.pow(arg_number as u32) % 10
     }
 
-    fn parse_argument(&self, arg_number: usize, access_flag: usize) -> isize {
-        let access_mode = if arg_number == 0 {
-            access_flag % 10
+    fn to_address(&self, target: &W) -> Result<usize, IntcodeError> {
+        let target = target.saturating_to_isize();
+        if target < 0 {
+            Err(IntcodeError::NegativeAddress {
+                target,
+                address: self.inst_pointer,
+            })
         } else {
-            access_flag / (10 * arg_number) % 10
-        };
+            Ok(target as usize)
+        }
+    }
 
+    fn parse_argument(&self, arg_number: usize, access_flag: usize) -> Result<W, IntcodeError> {
         let pointer = self.inst_pointer + arg_number + 1;
 
-        match access_mode {
-            // Position mode
-            0 => self.memory[self.memory[pointer] as usize],
+        match Self::access_mode(arg_number, access_flag) {
             // Immediate mode
-            1 => self.memory[pointer],
-            _ => panic!("Unknown access mode specified"),
+            1 => Ok(self.memory.get(pointer)?),
+            // Position and relative mode
+            _ => Ok(self
+                .memory
+                .get(self.parse_address(arg_number, access_flag)?)?),
         }
     }
 
-    /// Execute the program in memory
-    pub fn execute<F, G>(&mut self, input_fn: &mut F, output_fn: &mut G)
+    fn parse_address(&self, arg_number: usize, access_flag: usize) -> Result<usize, IntcodeError> {
+        let pointer = self.inst_pointer + arg_number + 1;
+
+        match Self::access_mode(arg_number, access_flag) {
+            // Position mode
+            0 => self.to_address(&self.memory.get(pointer)?),
+            // Relative mode
+            2 => {
+                let offset = self.memory.get(pointer)?.saturating_to_isize();
+                match self.relative_base.checked_add(offset) {
+                    Some(target) => self.to_address(&W::from_isize(target)),
+                    None => Err(IntcodeError::Overflow {
+                        address: self.inst_pointer,
+                    }),
+                }
+            }
+            // Immediate mode is not valid for parameters that are written to
+            mode => Err(IntcodeError::BadParameterMode {
+                mode,
+                address: self.inst_pointer,
+            }),
+        }
+    }
+
+    /// Execute the program in memory until it halts or faults
+    pub fn execute<F, G>(&mut self, input_fn: &mut F, output_fn: &mut G) -> Result<(), IntcodeError>
     where
-        F: FnMut() -> isize,
-        G: FnMut(isize),
+        F: FnMut() -> Option<W>,
+        G: FnMut(W),
     {
         loop {
-            self.step(input_fn, output_fn);
+            self.step(input_fn, output_fn)?;
 
-            if let ExecutionState::Halted(_) = self.exec_state {
-                break;
+            if self.exec_state != ExecutionState::Running {
+                return Ok(());
             }
         }
     }
 
+    /// Get the memory of the machine
+    pub fn memory(&self) -> &Memory<W> {
+        &self.memory
+    }
+
+    /// Get mutable access to the memory of the machine, for patching a paused program
+    pub fn memory_mut(&mut self) -> &mut Memory<W> {
+        self.limits.memory_changed();
+        &mut self.memory
+    }
+
+    /// Get the address of the next instruction to be executed
+    pub fn inst_pointer(&self) -> usize {
+        self.inst_pointer
+    }
+
+    /// Get the relative base used by parameters in relative mode
+    pub fn relative_base(&self) -> isize {
+        self.relative_base
+    }
+
     /// Get the current `ExecutionState` of the machine
     pub fn exec_state(&self) -> &ExecutionState {
         &self.exec_state
     }
 
     /// Get the result of the program (memory location 0)
-    pub fn result(&self) -> isize {
-        self.memory[0]
+    pub fn result(&self) -> W {
+        self.memory.get(0).unwrap_or_else(|_| W::from_isize(0))
     }
 }
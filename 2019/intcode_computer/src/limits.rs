@@ -0,0 +1,314 @@
+//! # Limits
+//! Bounding how long a machine may run.
+//!
+//! A machine can be given a budget of steps and a wall-clock deadline. When either runs out the
+//! machine stops before executing the next instruction, in the `ExecutionState::BudgetExhausted`
+//! state, and can carry on from exactly where it stopped once it is given a new budget or
+//! deadline. The deadline is only checked every `CLOCK_INTERVAL` steps, so a machine may run
+//! for a little longer than it allows.
+//!
+//! Loop detection looks for the machine returning to a state it has already been in; the same
+//! instruction pointer, relative base and memory. As the program can only behave differently
+//! given input, such a machine would repeat the same steps forever, so it faults with
+//! `IntcodeError::InfiniteLoop` instead. States are compared using Brent's algorithm, keeping a
+//! single checkpoint that is moved ever further apart, with a hash of memory that is updated on
+//! every write so that a full comparison is only needed when a repeat is likely. Consuming input
+//! or changing memory through `Machine::memory_mut` forgets what has been seen.
+use std::time::Instant;
+
+use crate::memory::Memory;
+use crate::{Event, ExecutionState, IntcodeError, Machine, Word};
+
+/// The number of steps between checks of the deadline
+pub const CLOCK_INTERVAL: usize = 256;
+
+// The limits set on a machine along with what is needed to enforce them
+#[derive(Debug, Clone)]
+pub(crate) struct Limits<W> {
+    steps: Option<usize>,
+    deadline: Option<Instant>,
+    // Steps until the deadline is next checked
+    until_clock: usize,
+    loops: Option<LoopDetector<W>>,
+}
+
+impl<W> Default for Limits<W> {
+    fn default() -> Self {
+        Limits {
+            steps: None,
+            deadline: None,
+            until_clock: 0,
+            loops: None,
+        }
+    }
+}
+
+impl<W: Word> Limits<W> {
+    // Whether any limit needs to be enforced
+    pub(crate) fn is_set(&self) -> bool {
+        self.steps.is_some() || self.deadline.is_some() || self.loops.is_some()
+    }
+
+    // Checks that the machine may execute its next instruction
+    pub(crate) fn before_step(
+        &mut self,
+        inst_pointer: usize,
+        relative_base: isize,
+        memory: &Memory<W>,
+    ) -> Result<(), IntcodeError> {
+        if self.steps == Some(0) {
+            return Err(IntcodeError::BudgetExhausted);
+        }
+        if let Some(deadline) = self.deadline {
+            if self.until_clock == 0 {
+                if Instant::now() >= deadline {
+                    return Err(IntcodeError::BudgetExhausted);
+                }
+                self.until_clock = CLOCK_INTERVAL;
+            }
+            self.until_clock -= 1;
+        }
+        if let Some(loops) = &mut self.loops {
+            if let Some(period) = loops.observe(inst_pointer, relative_base, memory) {
+                return Err(IntcodeError::InfiniteLoop {
+                    address: inst_pointer,
+                    period,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    // Accounts for an instruction that was attempted, and whether it consumed input
+    pub(crate) fn after_step(&mut self, event: &Option<Event<W>>, consumed: bool) {
+        if event == &Some(Event::NeedsInput) {
+            // The same state will be seen again once input arrives
+            self.forget();
+            return;
+        }
+        if let Some(steps) = &mut self.steps {
+            *steps -= 1;
+        }
+        if consumed {
+            self.forget();
+        }
+    }
+
+    // Updates the hash of memory for a write of `value` to `address` by the program
+    pub(crate) fn written(&mut self, address: usize, memory: &Memory<W>, value: &W) {
+        if let Some(loops) = &mut self.loops {
+            if let (Some(hash), Ok(old)) = (&mut loops.hash, memory.get(address)) {
+                *hash = hash
+                    .wrapping_sub(cell_hash(address, &old))
+                    .wrapping_add(cell_hash(address, value));
+            }
+        }
+    }
+
+    // Notes that memory was changed other than by the program
+    pub(crate) fn memory_changed(&mut self) {
+        if let Some(loops) = &mut self.loops {
+            loops.hash = None;
+        }
+        self.forget();
+    }
+
+    fn forget(&mut self) {
+        if let Some(loops) = &mut self.loops {
+            loops.checkpoint = None;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Checkpoint<W> {
+    inst_pointer: usize,
+    relative_base: isize,
+    hash: u64,
+    memory: Memory<W>,
+}
+
+#[derive(Debug, Clone)]
+struct LoopDetector<W> {
+    // The sum of `cell_hash` over every cell, if it is up to date
+    hash: Option<u64>,
+    checkpoint: Option<Checkpoint<W>>,
+    // Steps since the checkpoint was taken, and the number after which it is moved
+    distance: usize,
+    power: usize,
+}
+
+impl<W: Word> LoopDetector<W> {
+    fn new() -> LoopDetector<W> {
+        LoopDetector {
+            hash: None,
+            checkpoint: None,
+            distance: 0,
+            power: 1,
+        }
+    }
+
+    // Compares the state with the checkpoint, returning the number of steps between them if
+    // they are the same
+    fn observe(
+        &mut self,
+        inst_pointer: usize,
+        relative_base: isize,
+        memory: &Memory<W>,
+    ) -> Option<usize> {
+        let hash = *self.hash.get_or_insert_with(|| memory_hash(memory));
+
+        let checkpoint = match &self.checkpoint {
+            Some(checkpoint) => checkpoint,
+            None => {
+                self.distance = 0;
+                self.power = 1;
+                self.checkpoint = Some(Checkpoint {
+                    inst_pointer,
+                    relative_base,
+                    hash,
+                    memory: memory.clone(),
+                });
+                return None;
+            }
+        };
+
+        self.distance += 1;
+        if checkpoint.inst_pointer == inst_pointer
+            && checkpoint.relative_base == relative_base
+            && checkpoint.hash == hash
+            && &checkpoint.memory == memory
+        {
+            return Some(self.distance);
+        }
+
+        if self.distance == self.power {
+            self.distance = 0;
+            self.power *= 2;
+            self.checkpoint = Some(Checkpoint {
+                inst_pointer,
+                relative_base,
+                hash,
+                memory: memory.clone(),
+            });
+        }
+
+        None
+    }
+}
+
+// Mixes an address and its contents into a hash; zero cells contribute nothing so that the
+// hash does not depend on how much memory has been allocated
+fn cell_hash<W: Word>(address: usize, value: &W) -> u64 {
+    let value = value.saturating_to_isize();
+    if value == 0 {
+        return 0;
+    }
+
+    let mut hash = (address as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ value as u64;
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+fn memory_hash<W: Word>(memory: &Memory<W>) -> u64 {
+    memory
+        .regions()
+        .into_iter()
+        .flat_map(|(start, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .map(move |(offset, value)| cell_hash(start + offset, value))
+        })
+        .fold(0, u64::wrapping_add)
+}
+
+impl<W: Word> Machine<W> {
+    /// Stop the machine once it has executed `steps` more instructions
+    /// ### Examples
+    /// ```
+    /// use intcode_computer::{ExecutionState, IntcodeError, Machine};
+    ///
+    /// // Counts down from 3, outputting each value
+    /// let program = vec![4, 10, 1001, 10, -1, 10, 1005, 10, 0, 99, 3];
+    /// let mut machine = Machine::new(program).with_step_budget(4);
+    /// let mut outputs = Vec::new();
+    /// let error = machine.execute(&mut || None, &mut |out| outputs.push(out)).unwrap_err();
+    /// assert_eq!(IntcodeError::BudgetExhausted, error);
+    /// assert_eq!(&ExecutionState::BudgetExhausted, machine.exec_state());
+    /// assert_eq!(vec![3, 2], outputs);
+    ///
+    /// // Given more steps it carries on where it stopped
+    /// machine.set_step_budget(Some(100));
+    /// machine.execute(&mut || None, &mut |out| outputs.push(out)).unwrap();
+    /// assert_eq!(vec![3, 2, 1], outputs);
+    /// assert_eq!(Some(94), machine.step_budget());
+    /// ```
+    pub fn with_step_budget(mut self, steps: usize) -> Machine<W> {
+        self.set_step_budget(Some(steps));
+        self
+    }
+
+    /// Stop the machine once `deadline` has passed
+    /// ### Examples
+    /// ```
+    /// use std::time::Instant;
+    ///
+    /// use intcode_computer::{ExecutionState, IntcodeError, Machine};
+    ///
+    /// // Jumps to itself forever
+    /// let mut machine = Machine::new(vec![1105, 1, 0]).with_deadline(Instant::now());
+    /// let error = machine.execute(&mut || None, &mut |_out| {}).unwrap_err();
+    /// assert_eq!(IntcodeError::BudgetExhausted, error);
+    /// assert_eq!(&ExecutionState::BudgetExhausted, machine.exec_state());
+    /// ```
+    pub fn with_deadline(mut self, deadline: Instant) -> Machine<W> {
+        self.set_deadline(Some(deadline));
+        self
+    }
+
+    /// Fault the machine with `IntcodeError::InfiniteLoop` when it returns to a state it has
+    /// been in before without consuming input in between
+    /// ### Examples
+    /// ```
+    /// use intcode_computer::{IntcodeError, Machine};
+    ///
+    /// // Flips the value at address 7 between 0 and 1 forever
+    /// let program = vec![1007, 7, 1, 7, 1105, 1, 0, 0];
+    /// let mut machine = Machine::new(program).with_loop_detection();
+    /// let error = machine.execute(&mut || None, &mut |_out| {}).unwrap_err();
+    /// assert!(matches!(error, IntcodeError::InfiniteLoop { period: 4, .. }));
+    /// ```
+    pub fn with_loop_detection(mut self) -> Machine<W> {
+        self.limits.loops = Some(LoopDetector::new());
+        self
+    }
+
+    /// Set the number of instructions the machine may execute from now on, or remove the limit.
+    /// A machine that ran out of budget is able to run again.
+    pub fn set_step_budget(&mut self, steps: Option<usize>) {
+        self.limits.steps = steps;
+        self.resume();
+    }
+
+    /// Set the time after which the machine stops, or remove the deadline. A machine that ran
+    /// out of budget is able to run again.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.limits.deadline = deadline;
+        self.limits.until_clock = 0;
+        self.resume();
+    }
+
+    /// Get the number of instructions the machine may still execute, if it is limited
+    pub fn step_budget(&self) -> Option<usize> {
+        self.limits.steps
+    }
+
+    fn resume(&mut self) {
+        if self.exec_state == ExecutionState::BudgetExhausted {
+            self.exec_state = ExecutionState::Running;
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! # Loader
+//! Reading intcode programs from text and from packed binary images.
+//!
+//! Programs are usually written as comma separated numbers. Whitespace around each number,
+//! including a trailing newline, is ignored, as is a single trailing comma. Anything else that
+//! is not a number is reported along with its position.
+//!
+//! Large programs can also be stored as a packed image, which is faster to load. An image is
+//! the bytes `INTCODE` and a version byte of 1, followed by the number of words as a
+//! little-endian `u64` and then each word as a little-endian `i64`. Loading by path, from a
+//! reader or from stdin detects which format is in use.
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{Machine, Word};
+
+const MAGIC: &[u8; 8] = b"INTCODE\x01";
+const WORD_SIZE: usize = 8;
+
+/// ## LoadError
+/// The reasons a program can fail to load
+#[derive(Debug)]
+pub enum LoadError {
+    /// The program could not be read
+    Io(io::Error),
+    /// The token at `index`, starting from 0, is not a number. `line` and `column` start from
+    /// 1.
+    BadToken {
+        index: usize,
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    /// The packed image is malformed
+    BadImage(String),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            LoadError::Io(why) => write!(f, "{}", why),
+            LoadError::BadToken {
+                index,
+                line,
+                column,
+                token,
+            } => write!(
+                f,
+                "token {} at line {}, column {} is not a number: {:?}",
+                index, line, column, token
+            ),
+            LoadError::BadImage(reason) => write!(f, "malformed image: {}", reason),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        LoadError::Io(error)
+    }
+}
+
+/// Parse a comma separated program
+/// ### Examples
+/// ```
+/// use intcode_computer::loader::{parse, LoadError};
+///
+/// assert_eq!(vec![1, 0, 0, 0, 99], parse::<isize>(" 1,0,0,0,99\n").unwrap());
+///
+/// match parse::<isize>("1,0,\n0,x,99") {
+///     Err(LoadError::BadToken { index, line, column, token }) => {
+///         assert_eq!((3, 2, 3, "x"), (index, line, column, token.as_str()))
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+pub fn parse<W: Word>(text: &str) -> Result<Vec<W>, LoadError> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tokens = text.split(',').collect::<Vec<_>>();
+    let mut offset = 0;
+    let mut program = Vec::with_capacity(tokens.len());
+    for (index, token) in tokens.iter().enumerate() {
+        let trimmed = token.trim();
+        let is_trailing = index > 0 && index == tokens.len() - 1 && trimmed.is_empty();
+
+        match trimmed.parse() {
+            Ok(word) => program.push(word),
+            Err(_) if is_trailing => {}
+            Err(_) => {
+                let start = offset + (token.len() - token.trim_start().len());
+                let (line, column) = position(text, start);
+                return Err(LoadError::BadToken {
+                    index,
+                    line,
+                    column,
+                    token: trimmed.to_string(),
+                });
+            }
+        }
+
+        offset += token.len() + 1;
+    }
+
+    Ok(program)
+}
+
+// Finds the line and column of a byte offset into `text`
+fn position(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+    (line, column)
+}
+
+/// Write `program` as a packed image
+pub fn pack<T: Write>(program: &[isize], mut writer: T) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&(program.len() as u64).to_le_bytes())?;
+    for &word in program {
+        writer.write_all(&(word as i64).to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Read a packed image
+/// ### Examples
+/// ```
+/// use intcode_computer::loader::{pack, unpack};
+///
+/// let mut image = Vec::new();
+/// pack(&[1, 0, 0, 0, 99], &mut image).unwrap();
+/// assert_eq!(vec![1, 0, 0, 0, 99], unpack(&image).unwrap());
+/// ```
+pub fn unpack(bytes: &[u8]) -> Result<Vec<isize>, LoadError> {
+    let bad_image = |reason: &str| LoadError::BadImage(String::from(reason));
+
+    let body = bytes
+        .strip_prefix(&MAGIC[..])
+        .ok_or_else(|| bad_image("not a packed intcode image"))?;
+    if body.len() < WORD_SIZE {
+        return Err(bad_image("missing length"));
+    }
+    let (length, words) = body.split_at(WORD_SIZE);
+
+    let mut length_bytes = [0; WORD_SIZE];
+    length_bytes.copy_from_slice(length);
+    let length = u64::from_le_bytes(length_bytes);
+    if words.len() as u64 != length.saturating_mul(WORD_SIZE as u64) {
+        return Err(bad_image("length does not match the number of words"));
+    }
+
+    words
+        .chunks(WORD_SIZE)
+        .map(|chunk| {
+            let mut word_bytes = [0; WORD_SIZE];
+            word_bytes.copy_from_slice(chunk);
+            let word = i64::from_le_bytes(word_bytes);
+            #[allow(clippy::unnecessary_fallible_conversions)]
+            isize::try_from(word).map_err(|_| bad_image("word does not fit in an isize"))
+        })
+        .collect()
+}
+
+// Loads either format, telling them apart by the header of a packed image
+fn load(bytes: &[u8]) -> Result<Vec<isize>, LoadError> {
+    if bytes.starts_with(MAGIC) {
+        return unpack(bytes);
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => parse(text),
+        Err(_) => Err(LoadError::BadImage(String::from(
+            "neither text nor a packed intcode image",
+        ))),
+    }
+}
+
+impl Machine {
+    /// Create a new `Machine` using the contents of a file as the initial memory from a
+    /// supplied `&Path`. The file may be text or a packed image.
+    pub fn from_path(path: &Path) -> Result<Machine, LoadError> {
+        Ok(Machine::new(load(&fs::read(path)?)?))
+    }
+
+    /// Create a new `Machine` using a program read from `reader`, as text or a packed image
+    pub fn from_reader<T: Read>(mut reader: T) -> Result<Machine, LoadError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Ok(Machine::new(load(&bytes)?))
+    }
+
+    /// Create a new `Machine` using a program read from stdin, as text or a packed image
+    pub fn from_stdin() -> Result<Machine, LoadError> {
+        Machine::from_reader(io::stdin().lock())
+    }
+}
+
+/// Parse a `Machine` from a comma separated program
+/// ### Examples
+/// ```
+/// use intcode_computer::Machine;
+///
+/// let mut machine = "1,9,10,3,\n2,3,11,0,\n99,\n30,40,50\n".parse::<Machine>().unwrap();
+/// machine.execute(&mut || None, &mut |_out| {}).unwrap();
+/// assert_eq!(3500, machine.result());
+/// ```
+impl<W: Word> FromStr for Machine<W> {
+    type Err = LoadError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Machine::from_words(parse(s)?))
+    }
+}
@@ -0,0 +1,177 @@
+//! # Memory
+//! Growable memory for the intcode computer.
+//!
+//! Addresses close to the start of memory are kept in a contiguous vector that grows on demand,
+//! while high addresses are stored in sparsely allocated pages so that a program touching a
+//! handful of distant cells does not require a huge allocation.
+//! Cells that have never been written read as zero.
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::word::Word;
+
+/// The default number of addressable cells in a `Memory`
+pub const DEFAULT_MEMORY_LIMIT: usize = 1 << 30;
+
+// Addresses below this are stored in the dense vector, everything else is paged
+const DENSE_LIMIT: usize = 1 << 16;
+const PAGE_SIZE: usize = 1 << 10;
+
+/// ## MemoryError
+/// An access was attempted at an address beyond the limit of the memory
+#[derive(PartialEq, Debug, Clone)]
+pub struct MemoryError {
+    /// The address that was accessed
+    pub address: usize,
+    /// The limit of the memory at the time of the access
+    pub limit: usize,
+}
+
+impl Display for MemoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "address {} is beyond the memory limit of {}",
+            self.address, self.limit
+        )
+    }
+}
+
+impl Error for MemoryError {}
+
+/// ## Memory
+/// Zero-filled memory that grows as it is written to, up to a configurable limit
+/// ### Examples
+/// ```
+/// use intcode_computer::memory::Memory;
+///
+/// let mut memory = Memory::new(vec![1, 2, 3]).with_limit(1_000_000);
+/// assert_eq!(Ok(0), memory.get(999_999));
+/// memory.set(999_999, 42).unwrap();
+/// assert_eq!(Ok(42), memory.get(999_999));
+/// assert!(memory.set(1_000_000, 42).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Memory<W = isize> {
+    dense: Vec<W>,
+    pages: HashMap<usize, Box<[W]>>,
+    limit: usize,
+}
+
+impl Memory {
+    /// Create a new `Memory` with the given initial contents and the default limit
+    pub fn new(initial_memory: Vec<isize>) -> Memory {
+        Memory::from_words(initial_memory)
+    }
+}
+
+impl<W: Word> Memory<W> {
+    /// Create a new `Memory` holding any type of `Word`, with the given initial contents and
+    /// the default limit
+    pub fn from_words(initial_memory: Vec<W>) -> Memory<W> {
+        let limit = DEFAULT_MEMORY_LIMIT.max(initial_memory.len());
+        Memory {
+            dense: initial_memory,
+            pages: HashMap::new(),
+            limit,
+        }
+    }
+
+    /// Set the number of addressable cells. The limit never falls below the size of the
+    /// initial contents.
+    pub fn with_limit(mut self, limit: usize) -> Memory<W> {
+        self.limit = limit.max(self.dense.len());
+        self
+    }
+
+    /// Get the number of addressable cells
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Get the contiguous region of memory starting at address 0, which holds the initial
+    /// contents along with any cells written close to them
+    pub fn image(&self) -> &[W] {
+        &self.dense
+    }
+
+    /// Read the value stored at `address`
+    pub fn get(&self, address: usize) -> Result<W, MemoryError> {
+        self.check(address)?;
+
+        if address < self.dense.len() {
+            Ok(self.dense[address].clone())
+        } else if address < DENSE_LIMIT {
+            Ok(W::from_isize(0))
+        } else {
+            Ok(self
+                .pages
+                .get(&(address / PAGE_SIZE))
+                .map_or(W::from_isize(0), |page| page[address % PAGE_SIZE].clone()))
+        }
+    }
+
+    /// Store `value` at `address`, allocating memory if required
+    pub fn set(&mut self, address: usize, value: W) -> Result<(), MemoryError> {
+        self.check(address)?;
+
+        if address < self.dense.len() {
+            self.dense[address] = value;
+        } else if address < DENSE_LIMIT {
+            self.dense.resize(address + 1, W::from_isize(0));
+            self.dense[address] = value;
+        } else {
+            self.pages
+                .entry(address / PAGE_SIZE)
+                .or_insert_with(|| vec![W::from_isize(0); PAGE_SIZE].into_boxed_slice())
+                [address % PAGE_SIZE] = value;
+        }
+
+        Ok(())
+    }
+
+    // Lists each allocated region of memory along with its starting address, in address order
+    pub(crate) fn regions(&self) -> Vec<(usize, &[W])> {
+        let mut pages = self
+            .pages
+            .iter()
+            .map(|(&page, cells)| (page * PAGE_SIZE, &cells[..]))
+            .collect::<Vec<_>>();
+        pages.sort_by_key(|&(address, _)| address);
+
+        let mut regions = vec![(0, &self.dense[..])];
+        regions.extend(pages);
+        regions
+    }
+
+    // Gets the pages holding at least one non-zero cell, which are all that affect the contents
+    fn used_pages(&self) -> BTreeMap<usize, &[W]> {
+        let zero = W::from_isize(0);
+        self.pages
+            .iter()
+            .filter(|(_, cells)| cells.iter().any(|cell| cell != &zero))
+            .map(|(&page, cells)| (page, &cells[..]))
+            .collect()
+    }
+
+    fn check(&self, address: usize) -> Result<(), MemoryError> {
+        if address < self.limit {
+            Ok(())
+        } else {
+            Err(MemoryError {
+                address,
+                limit: self.limit,
+            })
+        }
+    }
+}
+
+impl<W: Word> PartialEq for Memory<W> {
+    fn eq(&self, other: &Memory<W>) -> bool {
+        self.limit == other.limit
+            && self.dense == other.dense
+            && self.used_pages() == other.used_pages()
+    }
+}
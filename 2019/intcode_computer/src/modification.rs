@@ -0,0 +1,139 @@
+//! # Modification
+//! Detecting programs that overwrite their own instructions.
+//!
+//! Once enabled with `Machine::record_self_modification`, every instruction the machine
+//! executes marks the cells it occupies as code. Any later write by the program to one of
+//! those cells is recorded as a `CodeWrite`. Changes made through `Machine::memory_mut` are
+//! not recorded.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::{Machine, Word};
+
+/// ## CodeWrite
+/// A write to a cell that had already been executed as part of an instruction
+#[derive(PartialEq, Debug, Clone)]
+pub struct CodeWrite<W = isize> {
+    /// The address of the instruction that made the write
+    pub writer: usize,
+    /// The address written to
+    pub target: usize,
+    /// The address of the executed instruction that `target` is part of
+    pub instruction: usize,
+    /// The value written
+    pub value: W,
+}
+
+/// ## SelfModification
+/// The writes a program has made to its own code
+/// ### Examples
+/// ```
+/// use intcode_computer::Machine;
+///
+/// // The first pass doubles the value at address 16 and turns the add into a multiply, so the
+/// // second pass squares it
+/// let program = vec![1, 16, 16, 16, 1001, 0, 1, 0, 1008, 0, 3, 17, 1006, 17, 0, 99, 3, 0];
+/// let mut machine = Machine::new(program);
+/// machine.record_self_modification();
+/// machine.execute(&mut || None, &mut |_out| {}).unwrap();
+///
+/// let log = machine.self_modification().unwrap();
+/// let values = log.writes().iter().map(|write| write.value).collect::<Vec<_>>();
+/// assert_eq!(vec![2, 3], values);
+/// assert_eq!(
+///     "     4: patched the instruction at 0 with 2 writes to [0]\n",
+///     log.to_string()
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct SelfModification<W = isize> {
+    // The instruction each executed cell was last executed as part of
+    code: HashMap<usize, usize>,
+    writes: Vec<CodeWrite<W>>,
+}
+
+impl<W> Default for SelfModification<W> {
+    fn default() -> Self {
+        SelfModification {
+            code: HashMap::new(),
+            writes: Vec::new(),
+        }
+    }
+}
+
+impl<W: Word> SelfModification<W> {
+    /// Get every write to code, in the order they were made
+    pub fn writes(&self) -> &[CodeWrite<W>] {
+        &self.writes
+    }
+
+    /// Get the number of writes each instruction made to each piece of code, keyed by the
+    /// address of the writer and then the address of the instruction written to
+    pub fn summary(&self) -> BTreeMap<(usize, usize), usize> {
+        let mut summary = BTreeMap::new();
+        for write in &self.writes {
+            *summary
+                .entry((write.writer, write.instruction))
+                .or_insert(0) += 1;
+        }
+        summary
+    }
+
+    pub(crate) fn executed(&mut self, address: usize, size: usize) {
+        for cell in address..address + size {
+            self.code.insert(cell, address);
+        }
+    }
+
+    pub(crate) fn written(&mut self, writer: usize, target: usize, value: &W) {
+        if let Some(&instruction) = self.code.get(&target) {
+            self.writes.push(CodeWrite {
+                writer,
+                target,
+                instruction,
+                value: value.clone(),
+            });
+        }
+    }
+}
+
+impl<W: Word> Display for SelfModification<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        for ((writer, instruction), count) in self.summary() {
+            let mut targets = self
+                .writes
+                .iter()
+                .filter(|write| write.writer == writer && write.instruction == instruction)
+                .map(|write| write.target)
+                .collect::<Vec<_>>();
+            targets.sort_unstable();
+            targets.dedup();
+            let targets = targets
+                .iter()
+                .map(|target| format!("[{}]", target))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let plural = if count == 1 { "" } else { "s" };
+            writeln!(
+                f,
+                "{:>6}: patched the instruction at {} with {} write{} to {}",
+                writer, instruction, count, plural, targets
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Word> Machine<W> {
+    /// Start recording writes the program makes to cells it has executed as instructions
+    pub fn record_self_modification(&mut self) {
+        self.modification = Some(SelfModification::default());
+    }
+
+    /// Get the writes recorded since `Machine::record_self_modification` was called
+    pub fn self_modification(&self) -> Option<&SelfModification<W>> {
+        self.modification.as_ref()
+    }
+}
@@ -0,0 +1,362 @@
+//! # Network
+//! Wiring for several intcode computers that communicate through queues.
+//!
+//! A `Network` runs its machines round-robin, one event per machine per turn, until every
+//! machine has halted or the network can make no further progress.
+//! Values can either flow along links between machines, as with the amplifiers of day 7, or
+//! be sent as addressed packets on a bus monitored by a NAT, as with the network of day 23.
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::{Event, ExecutionState, IntcodeError, Machine};
+
+// A machine on a packet bus is idle after this many consecutive empty reads
+const IDLE_READS: usize = 2;
+
+/// ## NetworkError
+/// The reasons a `Network` can fail to run
+#[derive(PartialEq, Debug, Clone)]
+pub enum NetworkError {
+    /// The machine at `index` faulted
+    Machine { index: usize, error: IntcodeError },
+    /// The machine at `index` sent a packet to an address that is not on the bus
+    UnknownAddress { index: usize, address: isize },
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            NetworkError::Machine { index, error } => write!(f, "machine {}: {}", index, error),
+            NetworkError::UnknownAddress { index, address } => write!(
+                f,
+                "machine {} sent a packet to unknown address {}",
+                index, address
+            ),
+        }
+    }
+}
+
+impl Error for NetworkError {}
+
+/// ## Outcome
+/// The reason a `Network` stopped running
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Outcome {
+    /// Every machine reached the end of its program
+    AllHalted,
+    /// Every machine that is still running is waiting for input that will never arrive
+    Blocked,
+    /// The NAT was about to deliver the same packet twice in a row, so the network would
+    /// repeat itself forever
+    NatRepeated(isize, isize),
+}
+
+/// ## Nat
+/// Monitors the packets sent to a special address on a packet bus. When the bus goes idle
+/// the last packet it received is delivered to address 0.
+#[derive(Debug, Clone)]
+pub struct Nat {
+    address: isize,
+    received: Vec<(isize, isize)>,
+    delivered: Vec<(isize, isize)>,
+}
+
+impl Nat {
+    /// Get the address the NAT listens on
+    pub fn address(&self) -> isize {
+        self.address
+    }
+
+    /// Get every packet sent to the NAT, in order
+    pub fn received(&self) -> &[(isize, isize)] {
+        &self.received
+    }
+
+    /// Get every packet the NAT delivered to address 0, in order
+    pub fn delivered(&self) -> &[(isize, isize)] {
+        &self.delivered
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Routing {
+    Links,
+    PacketBus(Nat),
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum NodeState {
+    Ready,
+    Blocked,
+    Halted,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    machine: Machine,
+    state: NodeState,
+    queue: VecDeque<isize>,
+    outputs: Vec<isize>,
+    links: Vec<usize>,
+    packet: Vec<isize>,
+    idle_reads: usize,
+}
+
+/// ## Network
+/// A set of intcode computers connected through queues
+/// ### Examples
+/// A ring of amplifiers with a feedback loop
+/// ```
+/// use intcode_computer::network::{Network, Outcome};
+/// use intcode_computer::Machine;
+///
+/// let program = vec![
+///     3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28,
+///     1005, 28, 6, 99, 0, 0, 5,
+/// ];
+/// let machines = (0..5).map(|_| Machine::new(program.clone())).collect();
+/// let mut network = Network::ring(machines);
+/// for (index, phase) in [9, 8, 7, 6, 5].iter().enumerate() {
+///     network.push_input(index, *phase);
+/// }
+/// network.push_input(0, 0);
+///
+/// assert_eq!(Ok(Outcome::AllHalted), network.run());
+/// assert_eq!(Some(&139_629_729), network.outputs(4).last());
+/// ```
+///
+/// Machines on a packet bus that report to a NAT and then wait forever
+/// ```
+/// use intcode_computer::network::{Network, Outcome};
+/// use intcode_computer::Machine;
+///
+/// let program = vec![3, 100, 104, 255, 4, 100, 104, 7, 3, 101, 1106, 0, 8];
+/// let machines = (0..2).map(|_| Machine::new(program.clone())).collect();
+/// let mut network = Network::packet_bus(machines, 255);
+///
+/// assert_eq!(Ok(Outcome::NatRepeated(1, 7)), network.run());
+/// let nat = network.nat().unwrap();
+/// assert_eq!(&[(0, 7), (1, 7)], nat.received());
+/// assert_eq!(&[(1, 7)], nat.delivered());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Network {
+    nodes: Vec<Node>,
+    routing: Routing,
+}
+
+impl Network {
+    /// Create a new `Network` of unconnected machines
+    pub fn new(machines: Vec<Machine>) -> Network {
+        Network {
+            nodes: machines
+                .into_iter()
+                .map(|machine| Node {
+                    machine,
+                    state: NodeState::Ready,
+                    queue: VecDeque::new(),
+                    outputs: Vec::new(),
+                    links: Vec::new(),
+                    packet: Vec::new(),
+                    idle_reads: 0,
+                })
+                .collect(),
+            routing: Routing::Links,
+        }
+    }
+
+    /// Create a new `Network` where the output of each machine is the input of the next
+    pub fn chain(machines: Vec<Machine>) -> Network {
+        let mut network = Network::new(machines);
+        for index in 1..network.nodes.len() {
+            network.connect(index - 1, index);
+        }
+        network
+    }
+
+    /// Create a new `Network` where the output of each machine is the input of the next, and
+    /// the output of the last machine is fed back to the first
+    pub fn ring(machines: Vec<Machine>) -> Network {
+        let mut network = Network::chain(machines);
+        if !network.nodes.is_empty() {
+            network.connect(network.nodes.len() - 1, 0);
+        }
+        network
+    }
+
+    /// Create a new `Network` where each machine is given its index as an address and sends
+    /// packets of the form `destination, x, y`. Packets sent to `nat_address` are handled by a
+    /// `Nat`. A machine that reads input when none is waiting receives -1.
+    pub fn packet_bus(machines: Vec<Machine>, nat_address: isize) -> Network {
+        let mut network = Network::new(machines);
+        network.routing = Routing::PacketBus(Nat {
+            address: nat_address,
+            received: Vec::new(),
+            delivered: Vec::new(),
+        });
+        for index in 0..network.nodes.len() {
+            network.push_input(index, index as isize);
+        }
+        network
+    }
+
+    /// Send every output of the machine at `from` to the machine at `to`.
+    /// Links are ignored on a packet bus.
+    pub fn connect(&mut self, from: usize, to: usize) {
+        self.nodes[from].links.push(to);
+    }
+
+    /// Queue an input for the machine at `index`
+    pub fn push_input(&mut self, index: usize, value: isize) {
+        self.nodes[index].queue.push_back(value);
+    }
+
+    /// Get the machine at `index`
+    pub fn machine(&self, index: usize) -> &Machine {
+        &self.nodes[index].machine
+    }
+
+    /// Get every value output by the machine at `index`, in order
+    pub fn outputs(&self, index: usize) -> &[isize] {
+        &self.nodes[index].outputs
+    }
+
+    /// Get the NAT monitoring a packet bus
+    pub fn nat(&self) -> Option<&Nat> {
+        match &self.routing {
+            Routing::PacketBus(nat) => Some(nat),
+            Routing::Links => None,
+        }
+    }
+
+    /// Run the machines round-robin until they have all halted or the network stops making
+    /// progress
+    pub fn run(&mut self) -> Result<Outcome, NetworkError> {
+        loop {
+            if self
+                .nodes
+                .iter()
+                .all(|node| node.state == NodeState::Halted)
+            {
+                return Ok(Outcome::AllHalted);
+            }
+
+            let mut progress = false;
+            for index in 0..self.nodes.len() {
+                progress |= self.turn(index)?;
+            }
+
+            if let Routing::PacketBus(nat) = &mut self.routing {
+                let idle = self.nodes.iter().all(|node| {
+                    node.state == NodeState::Halted
+                        || (node.queue.is_empty() && node.idle_reads >= IDLE_READS)
+                });
+                if !idle {
+                    continue;
+                }
+
+                let (x, y) = match nat.received.last() {
+                    Some(&packet) => packet,
+                    None => return Ok(Outcome::Blocked),
+                };
+                if nat.delivered.last() == Some(&(x, y)) {
+                    return Ok(Outcome::NatRepeated(x, y));
+                }
+
+                nat.delivered.push((x, y));
+                if let Some(node) = self.nodes.first_mut() {
+                    node.queue.extend(&[x, y]);
+                }
+                for node in self.nodes.iter_mut() {
+                    node.idle_reads = 0;
+                }
+            } else if !progress {
+                return Ok(Outcome::Blocked);
+            }
+        }
+    }
+
+    // Runs the machine at `index` until its next event, returning whether it could run
+    fn turn(&mut self, index: usize) -> Result<bool, NetworkError> {
+        let bus = matches!(self.routing, Routing::PacketBus(_));
+        let node = &mut self.nodes[index];
+
+        match node.state {
+            NodeState::Halted => return Ok(false),
+            NodeState::Blocked if node.queue.is_empty() && !bus => return Ok(false),
+            _ => {}
+        }
+
+        if node.machine.exec_state() != &ExecutionState::Running {
+            node.state = NodeState::Halted;
+            return Ok(false);
+        }
+
+        if node.state == NodeState::Blocked {
+            if let Some(value) = node.queue.pop_front() {
+                node.machine.push_input(value);
+                node.state = NodeState::Ready;
+            }
+        }
+
+        let event = node
+            .machine
+            .run_until_event()
+            .map_err(|error| NetworkError::Machine { index, error })?;
+
+        match event {
+            Event::NeedsInput => {
+                if let Some(value) = node.queue.pop_front() {
+                    node.machine.push_input(value);
+                    node.state = NodeState::Ready;
+                    node.idle_reads = 0;
+                } else if bus {
+                    node.machine.push_input(-1);
+                    node.idle_reads += 1;
+                } else {
+                    node.state = NodeState::Blocked;
+                }
+            }
+            Event::Output(value) => {
+                node.outputs.push(value);
+                self.route(index, value)?;
+            }
+            Event::Halted => node.state = NodeState::Halted,
+        }
+
+        Ok(true)
+    }
+
+    fn route(&mut self, index: usize, value: isize) -> Result<(), NetworkError> {
+        match &mut self.routing {
+            Routing::Links => {
+                for link in 0..self.nodes[index].links.len() {
+                    let to = self.nodes[index].links[link];
+                    self.nodes[to].queue.push_back(value);
+                }
+            }
+            Routing::PacketBus(nat) => {
+                let node = &mut self.nodes[index];
+                node.idle_reads = 0;
+                node.packet.push(value);
+                if node.packet.len() < 3 {
+                    return Ok(());
+                }
+
+                let packet = std::mem::take(&mut node.packet);
+                let (address, x, y) = (packet[0], packet[1], packet[2]);
+                if address == nat.address {
+                    nat.received.push((x, y));
+                } else if address >= 0 && (address as usize) < self.nodes.len() {
+                    self.nodes[address as usize].queue.extend(&[x, y]);
+                } else {
+                    return Err(NetworkError::UnknownAddress { index, address });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
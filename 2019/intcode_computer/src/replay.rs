@@ -0,0 +1,369 @@
+//! # Replay
+//! Recording the input and output of a run so that it can be reproduced exactly.
+//!
+//! `Machine::execute_recorded` runs a program like `Machine::execute`, noting every value
+//! consumed and produced along with the step at which it happened, counted from 0 as the
+//! number of instructions executed before it. `Machine::replay` runs a machine in the same
+//! starting state from the recorded inputs alone, checking that it behaves the same.
+//!
+//! Sessions are saved in a line based text format. The last line gives the number of steps
+//! executed and the state the machine finished in.
+//!
+//! ```text
+//! intcode-session 1
+//! 0 in 8
+//! 2 out 1
+//! 4 end halted
+//! ```
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::snapshot::{parse_state, write_state};
+use crate::{ExecutionState, IntcodeError, Machine, Word};
+
+const HEADER: &str = "intcode-session 1";
+
+/// ## SessionError
+/// The reasons a `Session` can fail to load
+#[derive(Debug)]
+pub enum SessionError {
+    /// The session could not be read or written
+    Io(io::Error),
+    /// The session is malformed at the given line, starting from 1
+    Parse { line: usize, reason: String },
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            SessionError::Io(why) => write!(f, "{}", why),
+            SessionError::Parse { line, reason } => write!(f, "line {}: {}", line, reason),
+        }
+    }
+}
+
+impl Error for SessionError {}
+
+impl From<io::Error> for SessionError {
+    fn from(error: io::Error) -> Self {
+        SessionError::Io(error)
+    }
+}
+
+/// ## ReplayError
+/// A difference between a replayed run and its session, at the step where it was found
+#[derive(PartialEq, Debug, Clone)]
+pub struct ReplayError {
+    pub step: usize,
+    /// What the session recorded, in the session format
+    pub expected: String,
+    /// What the machine did instead
+    pub found: String,
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "replay diverged at step {}: expected {}, found {}",
+            self.step, self.expected, self.found
+        )
+    }
+}
+
+impl Error for ReplayError {}
+
+/// ## Record
+/// A value passed into or out of the machine
+#[derive(PartialEq, Debug, Clone)]
+pub enum Record<W = isize> {
+    /// A value consumed by an input instruction
+    Input(W),
+    /// A value produced by an output instruction
+    Output(W),
+}
+
+impl<W: Word> Display for Record<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Record::Input(value) => write!(f, "in {}", value),
+            Record::Output(value) => write!(f, "out {}", value),
+        }
+    }
+}
+
+/// ## Session
+/// Every value a run consumed and produced, with the step each happened at, and how the run
+/// ended
+/// ### Examples
+/// ```
+/// use intcode_computer::replay::Session;
+/// use intcode_computer::Machine;
+///
+/// // Output 1 if the input is 8, else 0
+/// let program = vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+/// let (session, result) = Machine::new(program.clone()).execute_recorded(
+///     &mut || Some(8),
+///     &mut |_out| {},
+/// );
+/// assert_eq!(Ok(()), result);
+/// assert_eq!(
+///     "intcode-session 1\n0 in 8\n2 out 1\n4 end halted\n",
+///     session.to_string()
+/// );
+///
+/// let session = session.to_string().parse::<Session>().unwrap();
+/// assert_eq!(Ok(vec![1]), Machine::new(program).replay(&session));
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+pub struct Session<W = isize> {
+    records: Vec<(usize, Record<W>)>,
+    steps: usize,
+    end: ExecutionState,
+}
+
+impl<W: Word> Session<W> {
+    /// Get the values passed in and out of the machine, with the step each happened at
+    pub fn records(&self) -> &[(usize, Record<W>)] {
+        &self.records
+    }
+
+    /// Get the values consumed by the program, in order
+    pub fn inputs(&self) -> Vec<W> {
+        self.records
+            .iter()
+            .filter_map(|(_, record)| match record {
+                Record::Input(value) => Some(value.clone()),
+                Record::Output(_) => None,
+            })
+            .collect()
+    }
+
+    /// Get the values produced by the program, in order
+    pub fn outputs(&self) -> Vec<W> {
+        self.records
+            .iter()
+            .filter_map(|(_, record)| match record {
+                Record::Output(value) => Some(value.clone()),
+                Record::Input(_) => None,
+            })
+            .collect()
+    }
+
+    /// Get the number of instructions executed during the run
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Get the state the machine finished in
+    pub fn end(&self) -> &ExecutionState {
+        &self.end
+    }
+
+    /// Write the session to a file
+    pub fn save(&self, path: &Path) -> Result<(), SessionError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// Read a session from a file
+    pub fn load(path: &Path) -> Result<Session<W>, SessionError> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+// The session format for the end of a run
+struct End<'a>(&'a ExecutionState);
+
+impl Display for End<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "end ")?;
+        write_state(f, self.0)
+    }
+}
+
+impl<W: Word> Machine<W> {
+    /// Execute the program until it halts or faults, as `Machine::execute` does, recording
+    /// every value consumed and produced. The session is returned even if the machine faulted.
+    pub fn execute_recorded<F, G>(
+        &mut self,
+        input_fn: &mut F,
+        output_fn: &mut G,
+    ) -> (Session<W>, Result<(), IntcodeError>)
+    where
+        F: FnMut() -> Option<W>,
+        G: FnMut(W),
+    {
+        let mut records = Vec::new();
+        let mut steps = 0;
+        let mut result = Ok(());
+
+        while self.exec_state == ExecutionState::Running {
+            let mut input = || {
+                let value = input_fn();
+                if let Some(value) = &value {
+                    records.push((steps, Record::Input(value.clone())));
+                }
+                value
+            };
+            let mut output = None;
+            result = self.step(&mut input, &mut |out| output = Some(out));
+
+            if let Some(out) = output {
+                records.push((steps, Record::Output(out.clone())));
+                output_fn(out);
+            }
+            steps += 1;
+        }
+        if steps == 0 {
+            result = Err(IntcodeError::NotRunning);
+        }
+
+        let session = Session {
+            records,
+            steps,
+            end: self.exec_state.clone(),
+        };
+        (session, result)
+    }
+
+    /// Execute the program from the inputs recorded in `session`, checking that every value
+    /// is consumed and produced at the same step as when it was recorded, and that the run
+    /// ends the same way. The machine must start in the state the session was recorded from.
+    /// Returns the values output.
+    pub fn replay(&mut self, session: &Session<W>) -> Result<Vec<W>, ReplayError> {
+        let mut records = session.records.iter().peekable();
+        let mut outputs = Vec::new();
+        let mut steps = 0;
+
+        let expected = |next: Option<&&(usize, Record<W>)>| match next {
+            Some((step, record)) => format!("{} {}", step, record),
+            None => format!("{} {}", session.steps, End(&session.end)),
+        };
+
+        while self.exec_state == ExecutionState::Running && steps < session.steps {
+            let mut input = || match records.peek() {
+                Some((step, Record::Input(value))) if *step == steps => {
+                    records.next();
+                    Some(value.clone())
+                }
+                _ => None,
+            };
+            let mut output = None;
+            // Faults are compared with the end of the session below
+            let _ = self.step(&mut input, &mut |out| output = Some(out));
+
+            if let Some(out) = output {
+                let found = Record::Output(out.clone());
+                match records.peek() {
+                    Some((step, record)) if *step == steps && record == &found => {
+                        records.next();
+                        outputs.push(out);
+                    }
+                    next => {
+                        return Err(ReplayError {
+                            step: steps,
+                            expected: expected(next),
+                            found: format!("{} {}", steps, found),
+                        })
+                    }
+                }
+            }
+            steps += 1;
+        }
+
+        if let Some(next) = records.peek() {
+            return Err(ReplayError {
+                step: steps,
+                expected: expected(Some(next)),
+                found: format!("{} {}", steps, End(&self.exec_state)),
+            });
+        }
+        if steps != session.steps || self.exec_state != session.end {
+            return Err(ReplayError {
+                step: steps,
+                expected: expected(None),
+                found: format!("{} {}", steps, End(&self.exec_state)),
+            });
+        }
+
+        Ok(outputs)
+    }
+}
+
+impl<W: Word> Display for Session<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        writeln!(f, "{}", HEADER)?;
+        for (step, record) in &self.records {
+            writeln!(f, "{} {}", step, record)?;
+        }
+        writeln!(f, "{} {}", self.steps, End(&self.end))
+    }
+}
+
+impl<W: Word> FromStr for Session<W> {
+    type Err = SessionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |line: usize, reason: &str| SessionError::Parse {
+            line,
+            reason: String::from(reason),
+        };
+
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.split_whitespace().collect::<Vec<_>>()));
+        match lines.next() {
+            Some((_, words)) if words.join(" ") == HEADER => {}
+            _ => return Err(error(1, "not an intcode session")),
+        }
+
+        let mut records = Vec::new();
+        let mut last_step = 0;
+        for (line, words) in lines {
+            let step = words
+                .first()
+                .and_then(|word| word.parse::<usize>().ok())
+                .ok_or_else(|| error(line, "expected a step number"))?;
+            if step < last_step {
+                return Err(error(line, "steps are out of order"));
+            }
+            last_step = step;
+
+            let value = || -> Result<W, SessionError> {
+                match words[2..] {
+                    [word] => word.parse().map_err(|_| error(line, "expected a number")),
+                    _ => Err(error(line, "expected a single number")),
+                }
+            };
+            match words.get(1) {
+                Some(&"in") => records.push((step, Record::Input(value()?))),
+                Some(&"out") => records.push((step, Record::Output(value()?))),
+                Some(&"end") => {
+                    let end = parse_state(&words[2..])
+                        .ok_or_else(|| error(line, "unknown execution state"))?;
+                    if line != s.lines().count() {
+                        return Err(error(line + 1, "expected nothing after the end"));
+                    }
+                    return Ok(Session {
+                        records,
+                        steps: step,
+                        end,
+                    });
+                }
+                _ => return Err(error(line, "expected in, out or end")),
+            }
+        }
+
+        Err(error(
+            s.lines().count() + 1,
+            "expected end but the session ended",
+        ))
+    }
+}
@@ -0,0 +1,156 @@
+//! # Search
+//! Running a program once for every point in a parameter space, across several threads.
+//!
+//! Each point is applied to a copy of a starting `Machine`, which is then run on the
+//! `FastMachine` engine until it halts, faults or needs input that has not been pushed. A
+//! predicate on the final state picks out the matches.
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::fast::FastMachine;
+use crate::{Event, IntcodeError, Machine};
+
+/// ## Match
+/// A point of the parameter space accepted by the predicate of a `Search`
+#[derive(Debug, Clone)]
+pub struct Match<P> {
+    /// The position of the point in the parameter space
+    pub index: usize,
+    /// The point itself
+    pub params: P,
+    /// The final state of the machine
+    pub machine: Machine,
+}
+
+/// ## Search
+/// A parameter space to run a program over
+/// ### Examples
+/// ```
+/// use intcode_computer::search::Search;
+/// use intcode_computer::Machine;
+///
+/// // Add together the values at the noun and verb addresses
+/// let machine = Machine::new(vec![1, 0, 0, 0, 99, 10, 20, 30]);
+/// let search = Search::noun_verb(0..=7);
+/// let setup = |machine: &mut Machine, &(noun, verb): &(isize, isize)| {
+///     machine.set_noun_verb(noun, verb)
+/// };
+/// let found = search.first(&machine, setup, |machine| machine.result() == 50);
+/// assert_eq!(Some((6, 7)), found.map(|found| found.params));
+///
+/// let found = search.all(&machine, setup, |machine| machine.result() == 50);
+/// let params = found.iter().map(|found| found.params).collect::<Vec<_>>();
+/// assert_eq!(vec![(6, 7), (7, 6)], params);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Search<P> {
+    space: Vec<P>,
+    threads: usize,
+}
+
+impl Search<(isize, isize)> {
+    /// Create a new `Search` over every pair of noun and verb in `range`, ordered by noun and
+    /// then by verb
+    pub fn noun_verb(range: RangeInclusive<isize>) -> Search<(isize, isize)> {
+        let space = range
+            .clone()
+            .flat_map(|noun| range.clone().map(move |verb| (noun, verb)))
+            .collect();
+
+        Search::new(space)
+    }
+}
+
+impl<P: Clone + Send + Sync> Search<P> {
+    /// Create a new `Search` over the points in `space`, using one thread per available core
+    pub fn new(space: Vec<P>) -> Search<P> {
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        Search { space, threads }
+    }
+
+    /// Set the number of threads to search with
+    pub fn with_threads(mut self, threads: usize) -> Search<P> {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Find the earliest point in the space for which `predicate` accepts the final state of
+    /// the machine. `setup` applies a point to a copy of `machine`; points it fails for are
+    /// skipped. Points after a match are not run.
+    pub fn first<S, T>(&self, machine: &Machine, setup: S, predicate: T) -> Option<Match<P>>
+    where
+        S: Fn(&mut Machine, &P) -> Result<(), IntcodeError> + Sync,
+        T: Fn(&Machine) -> bool + Sync,
+    {
+        self.run(machine, &setup, &predicate, true)
+            .into_iter()
+            .next()
+    }
+
+    /// Find every point in the space for which `predicate` accepts the final state of the
+    /// machine, in the order they appear in the space
+    pub fn all<S, T>(&self, machine: &Machine, setup: S, predicate: T) -> Vec<Match<P>>
+    where
+        S: Fn(&mut Machine, &P) -> Result<(), IntcodeError> + Sync,
+        T: Fn(&Machine) -> bool + Sync,
+    {
+        self.run(machine, &setup, &predicate, false)
+    }
+
+    fn run<S, T>(&self, machine: &Machine, setup: &S, predicate: &T, first: bool) -> Vec<Match<P>>
+    where
+        S: Fn(&mut Machine, &P) -> Result<(), IntcodeError> + Sync,
+        T: Fn(&Machine) -> bool + Sync,
+    {
+        // Points are handed out in order, so once a match is found every earlier point has
+        // already been claimed and later points can be skipped
+        let next = AtomicUsize::new(0);
+        let earliest = AtomicUsize::new(usize::MAX);
+        let matches = Mutex::new(Vec::new());
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(self.space.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= self.space.len() || index > earliest.load(Ordering::Relaxed) {
+                        break;
+                    }
+
+                    let params = &self.space[index];
+                    let mut candidate = machine.clone();
+                    if setup(&mut candidate, params).is_err() {
+                        continue;
+                    }
+                    let candidate = run_to_end(candidate);
+
+                    if predicate(&candidate) {
+                        if first {
+                            earliest.fetch_min(index, Ordering::Relaxed);
+                        }
+                        matches.lock().unwrap().push(Match {
+                            index,
+                            params: params.clone(),
+                            machine: candidate,
+                        });
+                    }
+                });
+            }
+        });
+
+        let mut matches = matches.into_inner().unwrap();
+        matches.sort_by_key(|found| found.index);
+        if first {
+            matches.truncate(1);
+        }
+        matches
+    }
+}
+
+// Runs the machine until it stops producing output
+fn run_to_end(machine: Machine) -> Machine {
+    let mut machine = FastMachine::new(machine);
+    while let Ok(Event::Output(_)) = machine.run_until_event() {}
+    machine.into_machine()
+}
@@ -0,0 +1,356 @@
+//! # Snapshot
+//! Capturing and restoring the full state of a `Machine`.
+//!
+//! A `Snapshot` can be written to disk in a line based text format so that long running
+//! programs can be checkpointed and resumed, and so that the states of two runs can be compared
+//! with an ordinary diff tool.
+//!
+//! ```text
+//! intcode-snapshot 1
+//! state running
+//! ip 4
+//! relative-base 0
+//! inputs 5 7
+//! memory-limit 1073741824
+//! image 5
+//! 0: 1002 4 3 4 33
+//! end
+//! ```
+//!
+//! Memory is written 16 cells to a line, each line starting with the address of its first
+//! cell. Lines holding only zeros are left out.
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::memory::Memory;
+use crate::{ExecutionState, IntcodeError, Machine, Word};
+
+const HEADER: &str = "intcode-snapshot 1";
+const CELLS_PER_LINE: usize = 16;
+
+/// ## SnapshotError
+/// The reasons a `Snapshot` can fail to load
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The snapshot could not be read or written
+    Io(io::Error),
+    /// The snapshot is malformed at the given line, starting from 1
+    Parse { line: usize, reason: String },
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            SnapshotError::Io(why) => write!(f, "{}", why),
+            SnapshotError::Parse { line, reason } => write!(f, "line {}: {}", line, reason),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> Self {
+        SnapshotError::Io(error)
+    }
+}
+
+/// ## Snapshot
+/// The full state of a `Machine`; its memory, instruction pointer, relative base, pending
+/// input and execution state. Any trace sink is not part of the snapshot.
+/// ### Examples
+/// ```
+/// use intcode_computer::snapshot::Snapshot;
+/// use intcode_computer::Machine;
+///
+/// let mut machine = Machine::new(vec![1, 0, 0, 0, 99]);
+/// let snapshot = machine.snapshot();
+///
+/// machine.execute(&mut || None, &mut |_out| {}).unwrap();
+/// assert_eq!(2, machine.result());
+///
+/// machine.restore(&snapshot);
+/// assert_eq!(1, machine.result());
+///
+/// let saved = snapshot.to_string();
+/// assert_eq!(snapshot, saved.parse::<Snapshot>().unwrap());
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+pub struct Snapshot<W: Word = isize> {
+    memory: Memory<W>,
+    exec_state: ExecutionState,
+    inst_pointer: usize,
+    relative_base: isize,
+    inputs: Vec<W>,
+}
+
+impl<W: Word> Snapshot<W> {
+    /// Write the snapshot to a file
+    pub fn save(&self, path: &Path) -> Result<(), SnapshotError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// Read a snapshot from a file
+    pub fn load(path: &Path) -> Result<Snapshot<W>, SnapshotError> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl<W: Word> Machine<W> {
+    /// Capture the current state of the machine
+    pub fn snapshot(&self) -> Snapshot<W> {
+        Snapshot {
+            memory: self.memory.clone(),
+            exec_state: self.exec_state.clone(),
+            inst_pointer: self.inst_pointer,
+            relative_base: self.relative_base,
+            inputs: self.inputs.iter().cloned().collect(),
+        }
+    }
+
+    /// Return the machine to the state captured in `snapshot`. Any trace sink is kept.
+    pub fn restore(&mut self, snapshot: &Snapshot<W>) {
+        self.memory = snapshot.memory.clone();
+        self.exec_state = snapshot.exec_state.clone();
+        self.inst_pointer = snapshot.inst_pointer;
+        self.relative_base = snapshot.relative_base;
+        self.inputs = snapshot.inputs.iter().cloned().collect();
+        self.limits.memory_changed();
+    }
+
+    /// Create a new `Machine` in the state captured in `snapshot`
+    pub fn from_snapshot(snapshot: &Snapshot<W>) -> Machine<W> {
+        let mut machine = Machine::from_words(Vec::new());
+        machine.restore(snapshot);
+        machine
+    }
+}
+
+pub(crate) fn write_state(f: &mut Formatter<'_>, state: &ExecutionState) -> Result<(), fmt::Error> {
+    match state {
+        ExecutionState::Running => write!(f, "running"),
+        ExecutionState::Halted => write!(f, "halted"),
+        ExecutionState::BudgetExhausted => write!(f, "budget-exhausted"),
+        ExecutionState::Faulted(error) => match error {
+            IntcodeError::UnknownOpcode { opcode, address } => {
+                write!(f, "faulted unknown-opcode {} {}", opcode, address)
+            }
+            IntcodeError::BadParameterMode { mode, address } => {
+                write!(f, "faulted bad-parameter-mode {} {}", mode, address)
+            }
+            IntcodeError::NegativeAddress { target, address } => {
+                write!(f, "faulted negative-address {} {}", target, address)
+            }
+            IntcodeError::OutOfBounds { address, limit } => {
+                write!(f, "faulted out-of-bounds {} {}", address, limit)
+            }
+            IntcodeError::Overflow { address } => write!(f, "faulted overflow {}", address),
+            IntcodeError::InputExhausted => write!(f, "faulted input-exhausted"),
+            IntcodeError::NotRunning => write!(f, "faulted not-running"),
+            IntcodeError::BudgetExhausted => write!(f, "faulted budget-exhausted"),
+            IntcodeError::InfiniteLoop { address, period } => {
+                write!(f, "faulted infinite-loop {} {}", address, period)
+            }
+        },
+    }
+}
+
+pub(crate) fn parse_state(words: &[&str]) -> Option<ExecutionState> {
+    let number = |index: usize| words.get(index)?.parse::<isize>().ok();
+    let address = |index: usize| words.get(index)?.parse::<usize>().ok();
+
+    let error = match words {
+        ["running"] => return Some(ExecutionState::Running),
+        ["halted"] => return Some(ExecutionState::Halted),
+        ["budget-exhausted"] => return Some(ExecutionState::BudgetExhausted),
+        ["faulted", "unknown-opcode", _, _] => IntcodeError::UnknownOpcode {
+            opcode: number(2)?,
+            address: address(3)?,
+        },
+        ["faulted", "bad-parameter-mode", _, _] => IntcodeError::BadParameterMode {
+            mode: address(2)?,
+            address: address(3)?,
+        },
+        ["faulted", "negative-address", _, _] => IntcodeError::NegativeAddress {
+            target: number(2)?,
+            address: address(3)?,
+        },
+        ["faulted", "out-of-bounds", _, _] => IntcodeError::OutOfBounds {
+            address: address(2)?,
+            limit: address(3)?,
+        },
+        ["faulted", "overflow", _] => IntcodeError::Overflow {
+            address: address(2)?,
+        },
+        ["faulted", "input-exhausted"] => IntcodeError::InputExhausted,
+        ["faulted", "not-running"] => IntcodeError::NotRunning,
+        ["faulted", "budget-exhausted"] => IntcodeError::BudgetExhausted,
+        ["faulted", "infinite-loop", _, _] => IntcodeError::InfiniteLoop {
+            address: address(2)?,
+            period: address(3)?,
+        },
+        _ => return None,
+    };
+
+    Some(ExecutionState::Faulted(error))
+}
+
+impl<W: Word> Display for Snapshot<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        writeln!(f, "{}", HEADER)?;
+        write!(f, "state ")?;
+        write_state(f, &self.exec_state)?;
+        writeln!(f)?;
+        writeln!(f, "ip {}", self.inst_pointer)?;
+        writeln!(f, "relative-base {}", self.relative_base)?;
+        write!(f, "inputs")?;
+        for input in &self.inputs {
+            write!(f, " {}", input)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "memory-limit {}", self.memory.limit())?;
+        writeln!(f, "image {}", self.memory.image().len())?;
+
+        for (start, cells) in self.memory.regions() {
+            for (index, line) in cells.chunks(CELLS_PER_LINE).enumerate() {
+                if line.iter().all(|cell| cell == &W::from_isize(0)) {
+                    continue;
+                }
+
+                write!(f, "{}:", start + index * CELLS_PER_LINE)?;
+                for cell in line {
+                    write!(f, " {}", cell)?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        writeln!(f, "end")
+    }
+}
+
+impl<W: Word> FromStr for Snapshot<W> {
+    type Err = SnapshotError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate().map(|(index, line)| (index + 1, line));
+        let mut next = |expected: &str| -> Result<(usize, Vec<&str>), SnapshotError> {
+            match lines.next() {
+                Some((line, text)) => Ok((line, text.split_whitespace().collect())),
+                None => Err(SnapshotError::Parse {
+                    line: s.lines().count() + 1,
+                    reason: format!("expected {} but the snapshot ended", expected),
+                }),
+            }
+        };
+        let error = |line: usize, reason: &str| SnapshotError::Parse {
+            line,
+            reason: String::from(reason),
+        };
+
+        let (line, words) = next("header")?;
+        if words.join(" ") != HEADER {
+            return Err(error(line, "not an intcode snapshot"));
+        }
+
+        let mut field = |name: &str| -> Result<(usize, Vec<String>), SnapshotError> {
+            let (line, words) = next(name)?;
+            match words.split_first() {
+                Some((&first, rest)) if first == name => {
+                    Ok((line, rest.iter().map(|word| word.to_string()).collect()))
+                }
+                _ => Err(error(line, &format!("expected {}", name))),
+            }
+        };
+        let number = |(line, words): (usize, Vec<String>)| -> Result<isize, SnapshotError> {
+            match &words[..] {
+                [word] => word.parse().map_err(|_| error(line, "expected a number")),
+                _ => Err(error(line, "expected a single number")),
+            }
+        };
+        let address = |(line, words): (usize, Vec<String>)| -> Result<usize, SnapshotError> {
+            let value = number((line, words))?;
+            if value < 0 {
+                Err(error(line, "expected a non-negative number"))
+            } else {
+                Ok(value as usize)
+            }
+        };
+
+        let (line, words) = field("state")?;
+        let exec_state = parse_state(&words.iter().map(String::as_str).collect::<Vec<_>>())
+            .ok_or_else(|| error(line, "unknown execution state"))?;
+        let inst_pointer = address(field("ip")?)?;
+        let relative_base = number(field("relative-base")?)?;
+        let (line, words) = field("inputs")?;
+        let inputs = words
+            .iter()
+            .map(|word| word.parse().map_err(|_| error(line, "expected a number")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let limit = address(field("memory-limit")?)?;
+        let (image_line, words) = field("image")?;
+        let image_len = address((image_line, words))?;
+        if image_len > limit {
+            return Err(error(
+                image_line,
+                "the image is larger than the memory limit",
+            ));
+        }
+
+        let mut cells = Vec::new();
+        loop {
+            let (line, words) = next("memory or end")?;
+            if words == ["end"] {
+                break;
+            }
+
+            let start = words
+                .first()
+                .and_then(|word| word.strip_suffix(':'))
+                .and_then(|word| word.parse::<usize>().ok())
+                .ok_or_else(|| error(line, "expected an address followed by a colon"))?;
+            for (offset, word) in words[1..].iter().enumerate() {
+                let value = word
+                    .parse::<W>()
+                    .map_err(|_| error(line, "expected a number"))?;
+                let address = start
+                    .checked_add(offset)
+                    .ok_or_else(|| error(line, "the memory line runs past the largest address"))?;
+                cells.push((line, address, value));
+            }
+        }
+
+        let mut image = Vec::new();
+        image
+            .try_reserve_exact(image_len)
+            .map_err(|_| error(image_line, "the image is too large to allocate"))?;
+        image.resize(image_len, W::from_isize(0));
+        for (_, address, value) in &cells {
+            if *address < image_len {
+                image[*address] = value.clone();
+            }
+        }
+        let mut memory = Memory::from_words(image).with_limit(limit);
+        for (line, address, value) in cells {
+            if address >= image_len {
+                memory
+                    .set(address, value)
+                    .map_err(|why| error(line, &why.to_string()))?;
+            }
+        }
+
+        Ok(Snapshot {
+            memory,
+            exec_state,
+            inst_pointer,
+            relative_base,
+            inputs,
+        })
+    }
+}
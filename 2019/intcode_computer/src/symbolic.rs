@@ -0,0 +1,447 @@
+//! # Symbolic
+//! Solving for the noun and verb of a program without trying every pair.
+//!
+//! The program is executed symbolically, with memory cells 1 and 2 (as written by
+//! `Machine::set_noun_verb`) standing for the unknown noun and verb. Each cell holds a `Linear`
+//! expression over them, or is unknown once it holds something that is not linear or was read
+//! through an address that depends on them. Unknown cells are fine as long as the program never
+//! uses them, but branching on, addressing with or writing through a value that depends on the
+//! noun and verb cannot be followed. In that case `solve_noun_verb` falls back to a concrete
+//! `Search`.
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+
+use crate::instruction::{Mnemonic, Mode};
+use crate::search::Search;
+use crate::{IntcodeError, Machine};
+
+// The most instructions that will be executed before giving up
+const STEP_LIMIT: usize = 1_000_000;
+
+/// ## AnalysisError
+/// The reasons a program can not be analysed symbolically
+#[derive(PartialEq, Debug, Clone)]
+pub enum AnalysisError {
+    /// The instruction at `address` branches on, addresses memory with or writes through a
+    /// value that depends on the noun and verb
+    Symbolic { address: usize },
+    /// The result at address 0 is not a linear expression over the noun and verb
+    NotLinear,
+    /// The instruction at `address` reads input, or is not an instruction the analysis
+    /// understands
+    Unsupported { address: usize },
+    /// The program faulted regardless of the noun and verb
+    Fault(IntcodeError),
+    /// The program did not halt within the step limit
+    StepLimit,
+}
+
+impl Display for AnalysisError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            AnalysisError::Symbolic { address } => write!(
+                f,
+                "the instruction at address {} depends on the noun and verb",
+                address
+            ),
+            AnalysisError::NotLinear => write!(f, "the result is not linear"),
+            AnalysisError::Unsupported { address } => {
+                write!(f, "unsupported instruction at address {}", address)
+            }
+            AnalysisError::Fault(why) => write!(f, "{}", why),
+            AnalysisError::StepLimit => write!(f, "the program did not halt"),
+        }
+    }
+}
+
+impl Error for AnalysisError {}
+
+impl From<IntcodeError> for AnalysisError {
+    fn from(error: IntcodeError) -> Self {
+        AnalysisError::Fault(error)
+    }
+}
+
+/// ## Linear
+/// A linear expression over the noun and verb; each field is the coefficient of that term
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct Linear {
+    pub noun: isize,
+    pub verb: isize,
+    pub constant: isize,
+}
+
+impl Linear {
+    /// Create a new `Linear` that does not depend on the noun or verb
+    pub fn constant(value: isize) -> Linear {
+        Linear {
+            constant: value,
+            ..Linear::default()
+        }
+    }
+
+    /// Get the value of the expression if it does not depend on the noun or verb
+    pub fn as_constant(&self) -> Option<isize> {
+        if self.noun == 0 && self.verb == 0 {
+            Some(self.constant)
+        } else {
+            None
+        }
+    }
+
+    /// Evaluate the expression, returning `None` on overflow
+    pub fn evaluate(&self, noun: isize, verb: isize) -> Option<isize> {
+        self.noun
+            .checked_mul(noun)?
+            .checked_add(self.verb.checked_mul(verb)?)?
+            .checked_add(self.constant)
+    }
+
+    /// Find the noun and verb in `range` for which the expression equals `target`, trying
+    /// verbs in order and then nouns in order
+    pub fn solve(&self, target: isize, range: RangeInclusive<isize>) -> Option<(isize, isize)> {
+        for verb in range.clone() {
+            let remainder = match self
+                .with_verb(verb)
+                .and_then(|known| target.checked_sub(known))
+            {
+                Some(remainder) => remainder,
+                None => continue,
+            };
+            let noun = match self.noun {
+                0 if remainder == 0 => *range.start(),
+                0 => continue,
+                coefficient if remainder % coefficient == 0 => remainder / coefficient,
+                _ => continue,
+            };
+
+            if range.contains(&noun) {
+                return Some((noun, verb));
+            }
+        }
+
+        None
+    }
+
+    // Evaluates everything but the noun term
+    fn with_verb(&self, verb: isize) -> Option<isize> {
+        self.verb.checked_mul(verb)?.checked_add(self.constant)
+    }
+
+    fn checked_add(&self, other: &Linear) -> Option<Linear> {
+        Some(Linear {
+            noun: self.noun.checked_add(other.noun)?,
+            verb: self.verb.checked_add(other.verb)?,
+            constant: self.constant.checked_add(other.constant)?,
+        })
+    }
+
+    fn checked_sub(&self, other: &Linear) -> Option<Linear> {
+        Some(Linear {
+            noun: self.noun.checked_sub(other.noun)?,
+            verb: self.verb.checked_sub(other.verb)?,
+            constant: self.constant.checked_sub(other.constant)?,
+        })
+    }
+
+    // Multiplying is only linear when at least one side is a constant
+    fn checked_mul(&self, other: &Linear) -> Option<Option<Linear>> {
+        let (scale, linear) = match (self.as_constant(), other.as_constant()) {
+            (Some(scale), _) => (scale, other),
+            (_, Some(scale)) => (scale, self),
+            _ => return Some(None),
+        };
+
+        Some(Some(Linear {
+            noun: linear.noun.checked_mul(scale)?,
+            verb: linear.verb.checked_mul(scale)?,
+            constant: linear.constant.checked_mul(scale)?,
+        }))
+    }
+}
+
+impl Display for Linear {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let mut terms = Vec::new();
+        for &(coefficient, name) in &[(self.noun, "noun"), (self.verb, "verb")] {
+            match coefficient {
+                0 => {}
+                1 => terms.push(name.to_string()),
+                _ => terms.push(format!("{} * {}", coefficient, name)),
+            }
+        }
+        if self.constant != 0 || terms.is_empty() {
+            terms.push(self.constant.to_string());
+        }
+
+        write!(f, "{}", terms.join(" + "))
+    }
+}
+
+// The contents of a memory cell during symbolic execution
+#[derive(Debug, Clone, Copy)]
+enum Value {
+    Linear(Linear),
+    Unknown,
+}
+
+struct Executor<'a> {
+    machine: &'a Machine,
+    cells: HashMap<usize, Value>,
+    inst_pointer: usize,
+    relative_base: isize,
+}
+
+impl<'a> Executor<'a> {
+    fn read(&self, address: usize) -> Result<Value, AnalysisError> {
+        match self.cells.get(&address) {
+            Some(value) => Ok(*value),
+            None => {
+                let word = self
+                    .machine
+                    .memory()
+                    .get(address)
+                    .map_err(IntcodeError::from)?;
+                Ok(Value::Linear(Linear::constant(word)))
+            }
+        }
+    }
+
+    fn write(&mut self, address: usize, value: Value) -> Result<(), AnalysisError> {
+        let limit = self.machine.memory().limit();
+        if address >= limit {
+            return Err(IntcodeError::OutOfBounds { address, limit }.into());
+        }
+
+        self.cells.insert(address, value);
+        Ok(())
+    }
+
+    // Gets a value that must not depend on the noun or verb
+    fn concrete(&self, value: Value) -> Result<isize, AnalysisError> {
+        match value {
+            Value::Linear(linear) => linear.as_constant(),
+            Value::Unknown => None,
+        }
+        .ok_or(AnalysisError::Symbolic {
+            address: self.inst_pointer,
+        })
+    }
+
+    fn to_address(&self, target: isize) -> Result<usize, AnalysisError> {
+        if target < 0 {
+            Err(IntcodeError::NegativeAddress {
+                target,
+                address: self.inst_pointer,
+            }
+            .into())
+        } else {
+            Ok(target as usize)
+        }
+    }
+
+    // Resolves the address a parameter refers to, or `None` if it is an immediate
+    fn address(&self, mode: Mode, arg_number: usize) -> Result<Option<usize>, AnalysisError> {
+        let param = self.read(self.inst_pointer + arg_number + 1)?;
+        match mode {
+            Mode::Immediate => Ok(None),
+            Mode::Position => Ok(Some(self.to_address(self.concrete(param)?)?)),
+            Mode::Relative => {
+                let target = self
+                    .relative_base
+                    .checked_add(self.concrete(param)?)
+                    .ok_or(IntcodeError::Overflow {
+                        address: self.inst_pointer,
+                    })?;
+                Ok(Some(self.to_address(target)?))
+            }
+        }
+    }
+
+    // Reads a parameter. Values read through addresses that depend on the noun and verb are
+    // unknown, rather than an error, as the program may never use them.
+    fn argument(&self, mode: Mode, arg_number: usize) -> Result<Value, AnalysisError> {
+        match self.address(mode, arg_number) {
+            Ok(Some(address)) => self.read(address),
+            Ok(None) => self.read(self.inst_pointer + arg_number + 1),
+            Err(AnalysisError::Symbolic { .. }) => Ok(Value::Unknown),
+            Err(why) => Err(why),
+        }
+    }
+
+    fn run(&mut self) -> Result<Value, AnalysisError> {
+        for _ in 0..STEP_LIMIT {
+            let address = self.inst_pointer;
+            let unsupported = AnalysisError::Unsupported { address };
+            let overflow = IntcodeError::Overflow { address };
+
+            let word = self.concrete(self.read(address)?)?;
+            let mnemonic = match Mnemonic::from_opcode(word % 100) {
+                Some(mnemonic) if word >= 0 => mnemonic,
+                _ => return Err(unsupported),
+            };
+            let modes = (0..mnemonic.arity())
+                .map(|arg_number| Mode::from_digit(word / 10isize.pow(arg_number as u32 + 2) % 10))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| unsupported.clone())?;
+
+            match mnemonic {
+                Mnemonic::Add | Mnemonic::Mul | Mnemonic::Lt | Mnemonic::Eq => {
+                    let arg0 = self.argument(modes[0], 0)?;
+                    let arg1 = self.argument(modes[1], 1)?;
+                    let output_addr = self.address(modes[2], 2)?.ok_or(unsupported)?;
+
+                    let result = match (arg0, arg1) {
+                        (Value::Linear(arg0), Value::Linear(arg1)) => match mnemonic {
+                            Mnemonic::Add => {
+                                Value::Linear(arg0.checked_add(&arg1).ok_or(overflow)?)
+                            }
+                            Mnemonic::Mul => match arg0.checked_mul(&arg1).ok_or(overflow)? {
+                                Some(product) => Value::Linear(product),
+                                None => Value::Unknown,
+                            },
+                            // Comparisons are only known when the difference is a constant
+                            _ => {
+                                let difference = arg0.checked_sub(&arg1).ok_or(overflow)?;
+                                let difference = self.concrete(Value::Linear(difference))?;
+                                let result = match mnemonic {
+                                    Mnemonic::Lt => difference < 0,
+                                    _ => difference == 0,
+                                };
+                                Value::Linear(Linear::constant(result as isize))
+                            }
+                        },
+                        _ => match mnemonic {
+                            Mnemonic::Add | Mnemonic::Mul => Value::Unknown,
+                            _ => return Err(AnalysisError::Symbolic { address }),
+                        },
+                    };
+                    self.write(output_addr, result)?;
+
+                    self.inst_pointer += 4;
+                }
+                Mnemonic::In => return Err(unsupported),
+                Mnemonic::Out => {
+                    self.argument(modes[0], 0)?;
+                    self.inst_pointer += 2;
+                }
+                Mnemonic::Jnz | Mnemonic::Jz => {
+                    let is_zero = self.concrete(self.argument(modes[0], 0)?)? == 0;
+                    if is_zero == (mnemonic == Mnemonic::Jz) {
+                        let target = self.concrete(self.argument(modes[1], 1)?)?;
+                        self.inst_pointer = self.to_address(target)?;
+                    } else {
+                        self.inst_pointer += 3;
+                    }
+                }
+                Mnemonic::Arb => {
+                    let adjustment = self.concrete(self.argument(modes[0], 0)?)?;
+                    self.relative_base =
+                        self.relative_base.checked_add(adjustment).ok_or(overflow)?;
+                    self.inst_pointer += 2;
+                }
+                Mnemonic::Hlt => return self.read(0),
+            }
+        }
+
+        Err(AnalysisError::StepLimit)
+    }
+}
+
+/// Find the result of the program in `machine` at address 0 as a linear expression over the
+/// noun and verb
+/// ### Examples
+/// ```
+/// use intcode_computer::symbolic::{analyse, Linear};
+/// use intcode_computer::Machine;
+///
+/// let program = vec![1, 0, 0, 3, 1, 1, 2, 3, 1002, 1, 5, 0, 1, 0, 2, 0, 99];
+/// let result = analyse(&Machine::new(program)).unwrap();
+/// assert_eq!("5 * noun + verb", result.to_string());
+/// assert_eq!(Some((24, 3)), result.solve(123, 0..=99));
+/// ```
+pub fn analyse(machine: &Machine) -> Result<Linear, AnalysisError> {
+    let mut cells = HashMap::new();
+    cells.insert(
+        1,
+        Value::Linear(Linear {
+            noun: 1,
+            ..Linear::default()
+        }),
+    );
+    cells.insert(
+        2,
+        Value::Linear(Linear {
+            verb: 1,
+            ..Linear::default()
+        }),
+    );
+
+    let mut executor = Executor {
+        machine,
+        cells,
+        inst_pointer: machine.inst_pointer(),
+        relative_base: machine.relative_base(),
+    };
+    match executor.run()? {
+        Value::Linear(linear) => Ok(linear),
+        Value::Unknown => Err(AnalysisError::NotLinear),
+    }
+}
+
+/// Find the noun and verb in `range` for which the program in `machine` leaves `target` at
+/// address 0. Verbs are tried in order and then nouns, so where several pairs give `target` the
+/// one with the lowest verb is found. The program is analysed symbolically where possible,
+/// otherwise every pair is tried with a `Search`.
+/// ### Examples
+/// ```
+/// use intcode_computer::symbolic::{analyse, solve_noun_verb};
+/// use intcode_computer::Machine;
+///
+/// // Branches on the sum of the noun and verb, so can not be solved symbolically
+/// let program = vec![
+///     1101, 0, 0, 17, 1005, 17, 12, 1101, 0, 50, 0, 99, 1002, 17, 2, 0, 99, 0,
+/// ];
+/// let machine = Machine::new(program);
+/// assert!(analyse(&machine).is_err());
+/// assert_eq!(Some((0, 0)), solve_noun_verb(&machine, 50, 0..=99));
+/// assert_eq!(Some((21, 0)), solve_noun_verb(&machine, 42, 0..=99));
+/// ```
+pub fn solve_noun_verb(
+    machine: &Machine,
+    target: isize,
+    range: RangeInclusive<isize>,
+) -> Option<(isize, isize)> {
+    let setup =
+        |machine: &mut Machine, &(noun, verb): &(isize, isize)| machine.set_noun_verb(noun, verb);
+    let accept = |machine: &Machine| machine.result() == target;
+
+    if let Ok(linear) = analyse(machine) {
+        let solution = linear.solve(target, range.clone());
+
+        // Check the solution concretely, in case the program overflows for these values
+        match solution {
+            Some(params) => {
+                let mut candidate = machine.clone();
+                if setup(&mut candidate, &params).is_ok()
+                    && candidate.execute(&mut || None, &mut |_out| {}).is_ok()
+                    && accept(&candidate)
+                {
+                    return solution;
+                }
+            }
+            None => return None,
+        }
+    }
+
+    let space = range
+        .clone()
+        .flat_map(|verb| range.clone().map(move |noun| (noun, verb)))
+        .collect();
+    Search::new(space)
+        .first(machine, setup, accept)
+        .map(|found| found.params)
+}
@@ -0,0 +1,222 @@
+//! # Trace
+//! Opt-in tracing of the instructions executed by a `Machine`.
+//!
+//! A `TraceSink` attached with `Machine::set_trace_sink` receives a `TraceEntry` for every
+//! instruction the machine executes. `TraceLog` keeps every entry, `TraceWriter` writes them
+//! out as text and `Profile` aggregates them into instruction counts.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use crate::word::Word;
+
+/// ## TraceEntry
+/// A record of a single executed instruction
+#[derive(PartialEq, Debug, Clone)]
+pub struct TraceEntry<W = isize> {
+    /// The address of the instruction
+    pub address: usize,
+    /// The opcode of the instruction, without parameter modes
+    pub opcode: isize,
+    /// The mnemonic of the instruction, or the name of the registered operation
+    pub name: String,
+    /// The resolved parameters; the value read for inputs and the target address for outputs
+    pub operands: Vec<W>,
+    /// The address written to and the value stored there, if any
+    pub write: Option<(usize, W)>,
+}
+
+impl<W: Word> Display for TraceEntry<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{:>6}: {}", self.address, self.name)?;
+        for (index, operand) in self.operands.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{}{}", separator, operand)?;
+        }
+        if let Some((address, value)) = &self.write {
+            write!(f, " -> [{}] = {}", address, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// ## TraceSink
+/// Receives the instructions executed by a traced `Machine`
+pub trait TraceSink<W = isize> {
+    /// Called after each instruction is executed
+    fn record(&mut self, entry: &TraceEntry<W>);
+}
+
+// Wraps a shared sink so that `Machine` can remain `Debug` and `Clone`
+pub(crate) struct TraceHook<W>(pub(crate) Arc<Mutex<dyn TraceSink<W> + Send>>);
+
+impl<W> Clone for TraceHook<W> {
+    fn clone(&self) -> Self {
+        TraceHook(self.0.clone())
+    }
+}
+
+impl<W> TraceHook<W> {
+    pub(crate) fn record(&self, entry: &TraceEntry<W>) {
+        if let Ok(mut sink) = self.0.lock() {
+            sink.record(entry);
+        }
+    }
+}
+
+impl<W> fmt::Debug for TraceHook<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "TraceHook")
+    }
+}
+
+/// ## TraceLog
+/// Keeps every executed instruction
+/// ### Examples
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// use intcode_computer::trace::TraceLog;
+/// use intcode_computer::Machine;
+///
+/// let mut machine = Machine::new(vec![1002, 4, 3, 4, 33]);
+/// let log = Arc::new(Mutex::new(TraceLog::default()));
+/// machine.set_trace_sink(log.clone());
+/// machine.execute(&mut || None, &mut |_out| {}).unwrap();
+///
+/// let log = log.lock().unwrap();
+/// let lines = log.entries().iter().map(|entry| entry.to_string()).collect::<Vec<_>>();
+/// assert_eq!(vec!["     0: MUL 33, 3, 4 -> [4] = 99", "     4: HLT"], lines);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TraceLog<W = isize> {
+    entries: Vec<TraceEntry<W>>,
+}
+
+impl<W> TraceLog<W> {
+    /// Get the executed instructions, in order
+    pub fn entries(&self) -> &[TraceEntry<W>] {
+        &self.entries
+    }
+}
+
+impl<W> Default for TraceLog<W> {
+    fn default() -> Self {
+        TraceLog {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<W: Word> TraceSink<W> for TraceLog<W> {
+    fn record(&mut self, entry: &TraceEntry<W>) {
+        self.entries.push(entry.clone());
+    }
+}
+
+/// ## TraceWriter
+/// Writes each executed instruction as a line of text
+#[derive(Debug)]
+pub struct TraceWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// Create a new `TraceWriter` writing to `writer`
+    pub fn new(writer: W) -> TraceWriter<W> {
+        TraceWriter { writer }
+    }
+
+    /// Get the underlying writer back
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<T: Write, W: Word> TraceSink<W> for TraceWriter<T> {
+    fn record(&mut self, entry: &TraceEntry<W>) {
+        // Tracing must not disturb execution, so failures to write are ignored
+        let _ = writeln!(self.writer, "{}", entry);
+    }
+}
+
+/// ## Profile
+/// Aggregated statistics about the instructions executed by a machine
+/// ### Examples
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// use intcode_computer::trace::Profile;
+/// use intcode_computer::Machine;
+///
+/// // Count down from 3 to 0
+/// let mut machine = Machine::new(vec![1001, 8, -1, 8, 1005, 8, 0, 99, 3]);
+/// let profile = Arc::new(Mutex::new(Profile::default()));
+/// machine.set_trace_sink(profile.clone());
+/// machine.execute(&mut || None, &mut |_out| {}).unwrap();
+///
+/// let profile = profile.lock().unwrap();
+/// assert_eq!(7, profile.total_steps());
+/// assert_eq!(Some(&3), profile.opcode_counts().get(&1));
+/// assert_eq!(vec![(0, 3), (4, 3)], profile.hot_addresses(2));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    total_steps: usize,
+    opcode_counts: BTreeMap<isize, usize>,
+    address_counts: HashMap<usize, usize>,
+    names: BTreeMap<isize, String>,
+}
+
+impl Profile {
+    /// Get the total number of instructions executed
+    pub fn total_steps(&self) -> usize {
+        self.total_steps
+    }
+
+    /// Get the number of times each opcode was executed
+    pub fn opcode_counts(&self) -> &BTreeMap<isize, usize> {
+        &self.opcode_counts
+    }
+
+    /// Get the `count` most executed instruction addresses along with the number of times each
+    /// was executed, most executed first
+    pub fn hot_addresses(&self, count: usize) -> Vec<(usize, usize)> {
+        let mut addresses = self
+            .address_counts
+            .iter()
+            .map(|(&address, &executed)| (address, executed))
+            .collect::<Vec<_>>();
+        addresses.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        addresses.truncate(count);
+        addresses
+    }
+}
+
+impl<W> TraceSink<W> for Profile {
+    fn record(&mut self, entry: &TraceEntry<W>) {
+        self.total_steps += 1;
+        *self.opcode_counts.entry(entry.opcode).or_insert(0) += 1;
+        self.names
+            .entry(entry.opcode)
+            .or_insert_with(|| entry.name.clone());
+        *self.address_counts.entry(entry.address).or_insert(0) += 1;
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        writeln!(f, "Total steps: {}", self.total_steps)?;
+        writeln!(f, "Instructions:")?;
+        for (&opcode, count) in &self.opcode_counts {
+            writeln!(f, "  {:<6} {}", self.names[&opcode], count)?;
+        }
+        writeln!(f, "Hot addresses:")?;
+        for (address, count) in self.hot_addresses(10) {
+            writeln!(f, "  {:>6} {}", address, count)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! # Word
+//! The types that can be stored in the memory of a `Machine`.
+//!
+//! `isize`, `i64` and `i128` raise `IntcodeError::Overflow` when arithmetic does not fit in the
+//! word, rather than panicking or wrapping. With the `bigint` feature enabled `BigInt` can also
+//! be used, which never overflows.
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+/// ## Word
+/// A value stored in a single cell of intcode memory
+pub trait Word:
+    Clone + PartialEq + PartialOrd + Debug + Display + FromStr + Send + 'static
+{
+    /// Convert from an `isize`
+    fn from_isize(value: isize) -> Self;
+
+    /// Convert to an `isize`, if the value fits
+    fn to_isize(&self) -> Option<isize>;
+
+    /// Convert to an `isize`, clamping values that do not fit to the nearest bound
+    fn saturating_to_isize(&self) -> isize;
+
+    /// Add two words, returning `None` on overflow
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    /// Multiply two words, returning `None` on overflow
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+}
+
+macro_rules! primitive_word {
+    ($($word:ty),*) => {
+        $(
+            impl Word for $word {
+                fn from_isize(value: isize) -> Self {
+                    value as $word
+                }
+
+                #[allow(clippy::unnecessary_fallible_conversions)]
+                fn to_isize(&self) -> Option<isize> {
+                    use std::convert::TryFrom;
+                    isize::try_from(*self).ok()
+                }
+
+                fn saturating_to_isize(&self) -> isize {
+                    match self.to_isize() {
+                        Some(value) => value,
+                        None if *self < 0 => isize::MIN,
+                        None => isize::MAX,
+                    }
+                }
+
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    <$word>::checked_add(*self, *other)
+                }
+
+                fn checked_mul(&self, other: &Self) -> Option<Self> {
+                    <$word>::checked_mul(*self, *other)
+                }
+            }
+        )*
+    };
+}
+
+primitive_word!(isize, i64, i128);
+
+#[cfg(feature = "bigint")]
+mod bigint {
+    use num_bigint::BigInt;
+    use num_traits::{Signed, ToPrimitive};
+
+    use super::Word;
+
+    /// ### Examples
+    /// ```
+    /// use intcode_computer::Machine;
+    /// use num_bigint::BigInt;
+    ///
+    /// let program = [1102, 1 << 62, 1 << 62, 7, 4, 7, 99, 0]
+    ///     .iter()
+    ///     .map(|&word: &isize| BigInt::from(word))
+    ///     .collect();
+    /// let mut outputs = Vec::new();
+    /// Machine::from_words(program)
+    ///     .execute(&mut || None, &mut |out| outputs.push(out))
+    ///     .unwrap();
+    /// assert_eq!(vec![BigInt::from(1) << 124], outputs);
+    /// ```
+    impl Word for BigInt {
+        fn from_isize(value: isize) -> Self {
+            BigInt::from(value)
+        }
+
+        fn to_isize(&self) -> Option<isize> {
+            ToPrimitive::to_isize(self)
+        }
+
+        fn saturating_to_isize(&self) -> isize {
+            match ToPrimitive::to_isize(self) {
+                Some(value) => value,
+                None if self.is_negative() => isize::MIN,
+                None => isize::MAX,
+            }
+        }
+
+        fn checked_add(&self, other: &Self) -> Option<Self> {
+            Some(self + other)
+        }
+
+        fn checked_mul(&self, other: &Self) -> Option<Self> {
+            Some(self * other)
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! Checks the assembler rejects malformed source rather than panicking.
+use intcode_computer::asm::{assemble, AsmErrorKind};
+
+#[test]
+fn non_ascii_operands() {
+    for operand in ["[aé]", "[é]", "[ré]", "[rbé]", "[rb+é]", "#é", "[naïve]"].iter() {
+        let error = assemble(&format!("OUT {}", operand)).unwrap_err();
+        assert_eq!(1, error.line);
+        assert_eq!(AsmErrorKind::BadOperand(String::from(*operand)), error.kind);
+    }
+}
+
+#[test]
+fn non_ascii_labels() {
+    assert!(assemble("é: HLT").is_err());
+    assert!(assemble("HLT\nJNZ #1, #café").is_err());
+}
+
+#[test]
+fn label_offsets_that_overflow() {
+    let error = assemble("HLT\nend: JNZ #1, #end+9223372036854775807").unwrap_err();
+    assert_eq!(2, error.line);
+    assert_eq!(
+        AsmErrorKind::BadOperand(String::from("end+9223372036854775807")),
+        error.kind
+    );
+    assert!(assemble("HLT\nend: data end+9223372036854775806").is_ok());
+}
@@ -0,0 +1,294 @@
+//! Conformance corpus for the interpreter; every instruction with every combination of
+//! parameter modes, checked against a reference model, and the published examples from days
+//! 2, 5 and 9.
+use intcode_computer::instruction::{Mnemonic, MNEMONICS};
+use intcode_computer::{Event, ExecutionState, IntcodeError, Machine};
+
+// Each program sets the relative base then runs the instruction under test
+const RELATIVE_BASE: isize = 1000;
+const INSTRUCTION: usize = 2;
+// Parameter `i` refers to the cell `DATA + i` in position mode and the cell
+// `RELATIVE_BASE + DATA + i` in relative mode
+const DATA: isize = 20;
+const MEMORY_SIZE: usize = 1100;
+const INPUT: isize = 77;
+
+// The value each parameter takes in each mode
+struct Values {
+    position: [isize; 3],
+    immediate: [isize; 3],
+    relative: [isize; 3],
+}
+
+// Every mode gives a different value, and the second immediate is a negative jump target
+const DISTINCT: Values = Values {
+    position: [5, 7, 9],
+    immediate: [-3, 11, 4],
+    relative: [13, -17, 6],
+};
+
+// The first two parameters are equal in every mode
+const EQUAL: Values = Values {
+    position: [12, 12, 9],
+    immediate: [12, 12, 4],
+    relative: [12, 12, 6],
+};
+
+// The first parameter is zero in every mode, so jumps on zero are taken
+const ZERO: Values = Values {
+    position: [0, 12, 9],
+    immediate: [0, 12, 4],
+    relative: [0, 12, 6],
+};
+
+// The effect of executing a single instruction
+#[derive(PartialEq, Debug)]
+enum Outcome {
+    Write { address: usize, value: isize },
+    Output(isize),
+    Jump(usize),
+    NoJump,
+    RelativeBase(isize),
+    Halt,
+    Fault(IntcodeError),
+}
+
+// What the instruction should do according to the puzzle descriptions
+fn expected(mnemonic: Mnemonic, modes: &[usize], values: &Values) -> Outcome {
+    let value = |index: usize| match modes[index] {
+        0 => values.position[index],
+        1 => values.immediate[index],
+        _ => values.relative[index],
+    };
+    let write = |index: usize, value: isize| match modes[index] {
+        0 => Outcome::Write {
+            address: (DATA + index as isize) as usize,
+            value,
+        },
+        2 => Outcome::Write {
+            address: (RELATIVE_BASE + DATA + index as isize) as usize,
+            value,
+        },
+        mode => Outcome::Fault(IntcodeError::BadParameterMode {
+            mode,
+            address: INSTRUCTION,
+        }),
+    };
+    let jump = |condition: bool| match (condition, value(1)) {
+        (false, _) => Outcome::NoJump,
+        (true, target) if target < 0 => Outcome::Fault(IntcodeError::NegativeAddress {
+            target,
+            address: INSTRUCTION,
+        }),
+        (true, target) => Outcome::Jump(target as usize),
+    };
+
+    match mnemonic {
+        Mnemonic::Add => write(2, value(0) + value(1)),
+        Mnemonic::Mul => write(2, value(0) * value(1)),
+        Mnemonic::In => write(0, INPUT),
+        Mnemonic::Out => Outcome::Output(value(0)),
+        Mnemonic::Jnz => jump(value(0) != 0),
+        Mnemonic::Jz => jump(value(0) == 0),
+        Mnemonic::Lt => write(2, (value(0) < value(1)) as isize),
+        Mnemonic::Eq => write(2, (value(0) == value(1)) as isize),
+        Mnemonic::Arb => Outcome::RelativeBase(RELATIVE_BASE + value(0)),
+        Mnemonic::Hlt => Outcome::Halt,
+    }
+}
+
+// Runs the instruction on the interpreter, checking that it changes nothing else
+fn actual(mnemonic: Mnemonic, modes: &[usize], values: &Values) -> Outcome {
+    let mut program = vec![0; MEMORY_SIZE];
+    program[0] = 109;
+    program[1] = RELATIVE_BASE;
+    let mode_digits = modes
+        .iter()
+        .rev()
+        .fold(0, |digits, &mode| digits * 10 + mode);
+    program[INSTRUCTION] = mode_digits as isize * 100 + mnemonic.opcode();
+    for (index, &mode) in modes.iter().enumerate() {
+        let cell = DATA + index as isize;
+        program[INSTRUCTION + index + 1] = match mode {
+            1 => values.immediate[index],
+            _ => cell,
+        };
+        program[cell as usize] = values.position[index];
+        program[(RELATIVE_BASE + cell) as usize] = values.relative[index];
+    }
+
+    let mut machine = Machine::new(program.clone());
+    machine.step(&mut || None, &mut |_out| {}).unwrap();
+    let mut output = None;
+    let result = machine.step(&mut || Some(INPUT), &mut |out| output = Some(out));
+
+    let next = INSTRUCTION + modes.len() + 1;
+    let changed = machine
+        .memory()
+        .image()
+        .iter()
+        .zip(&program)
+        .enumerate()
+        .filter(|(_, (after, before))| after != before)
+        .map(|(address, (&value, _))| Outcome::Write { address, value })
+        .collect::<Vec<_>>();
+    if let Err(why) = result {
+        assert!(changed.is_empty());
+        return Outcome::Fault(why);
+    }
+    assert!(changed.len() <= 1, "{:?}", changed);
+    assert_eq!(MEMORY_SIZE, machine.memory().image().len());
+
+    if machine.exec_state() == &ExecutionState::Halted {
+        return Outcome::Halt;
+    }
+    if let Some(out) = output {
+        assert_eq!(next, machine.inst_pointer());
+        return Outcome::Output(out);
+    }
+    if let Some(write) = changed.into_iter().next() {
+        assert_eq!(next, machine.inst_pointer());
+        return write;
+    }
+    match mnemonic {
+        Mnemonic::Arb => {
+            assert_eq!(next, machine.inst_pointer());
+            Outcome::RelativeBase(machine.relative_base())
+        }
+        Mnemonic::Jnz | Mnemonic::Jz if machine.inst_pointer() == next => Outcome::NoJump,
+        _ => {
+            assert_eq!(RELATIVE_BASE, machine.relative_base());
+            Outcome::Jump(machine.inst_pointer())
+        }
+    }
+}
+
+#[test]
+fn every_mode_combination() {
+    let mut cases = 0;
+    for &mnemonic in MNEMONICS.iter() {
+        let arity = mnemonic.arity();
+        for combination in 0..3usize.pow(arity as u32) {
+            let modes = (0..arity)
+                .map(|index| combination / 3usize.pow(index as u32) % 3)
+                .collect::<Vec<_>>();
+            for values in [DISTINCT, EQUAL, ZERO].iter() {
+                assert_eq!(
+                    expected(mnemonic, &modes, values),
+                    actual(mnemonic, &modes, values),
+                    "{} with modes {:?}",
+                    mnemonic,
+                    modes
+                );
+                cases += 1;
+            }
+        }
+    }
+    assert_eq!(3 * (4 * 27 + 2 * 3 + 2 * 9 + 3 + 1), cases);
+}
+
+#[test]
+fn input() {
+    // Without input the instruction faults, or pauses and waits when driven by events
+    let mut machine = Machine::new(vec![3, 0, 99]);
+    assert_eq!(
+        Err(IntcodeError::InputExhausted),
+        machine.step(&mut || None, &mut |_out| {})
+    );
+
+    let mut machine = Machine::new(vec![3, 0, 99]);
+    assert_eq!(Ok(Event::NeedsInput), machine.run_until_event());
+    assert_eq!(0, machine.inst_pointer());
+    machine.push_input(5);
+    assert_eq!(Ok(Event::Halted), machine.run_until_event());
+    assert_eq!(5, machine.result());
+}
+
+// Whether a day 5 comparison example should output 1 for an input
+type Comparison = fn(isize) -> bool;
+
+// Runs a published example to the end, returning its outputs and final memory
+fn run(program: &[isize], inputs: &[isize]) -> (Vec<isize>, Vec<isize>) {
+    let mut machine = Machine::new(program.to_vec());
+    let mut inputs = inputs.iter().copied();
+    let mut outputs = Vec::new();
+    machine
+        .execute(&mut || inputs.next(), &mut |out| outputs.push(out))
+        .unwrap();
+    assert_eq!(&ExecutionState::Halted, machine.exec_state());
+    (outputs, machine.memory().image().to_vec())
+}
+
+#[test]
+fn day2_examples() {
+    let examples: &[(&[isize], &[isize])] = &[
+        (
+            &[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50],
+            &[3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50],
+        ),
+        (&[1, 0, 0, 0, 99], &[2, 0, 0, 0, 99]),
+        (&[2, 3, 0, 3, 99], &[2, 3, 0, 6, 99]),
+        (&[2, 4, 4, 5, 99, 0], &[2, 4, 4, 5, 99, 9801]),
+        (
+            &[1, 1, 1, 4, 99, 5, 6, 0, 99],
+            &[30, 1, 1, 4, 2, 5, 6, 0, 99],
+        ),
+    ];
+
+    for (program, memory) in examples.iter() {
+        assert_eq!(memory.to_vec(), run(program, &[]).1);
+    }
+}
+
+#[test]
+fn day5_examples() {
+    assert_eq!(vec![42], run(&[3, 0, 4, 0, 99], &[42]).0);
+    assert_eq!(vec![1002, 4, 3, 4, 99], run(&[1002, 4, 3, 4, 33], &[]).1);
+    assert_eq!(
+        vec![1101, 100, -1, 4, 99],
+        run(&[1101, 100, -1, 4, 0], &[]).1
+    );
+
+    // Each compares the input with 8, outputting 1 if it holds, else 0
+    let comparisons: &[(&[isize], Comparison)] = &[
+        (&[3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8], |input| input == 8),
+        (&[3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8], |input| input < 8),
+        (&[3, 3, 1108, -1, 8, 3, 4, 3, 99], |input| input == 8),
+        (&[3, 3, 1107, -1, 8, 3, 4, 3, 99], |input| input < 8),
+    ];
+    // Each outputs 0 if the input is 0, else 1
+    let jumps: &[&[isize]] = &[
+        &[3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9],
+        &[3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1],
+    ];
+    let larger = [
+        3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+        1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20,
+        1105, 1, 46, 98, 99,
+    ];
+
+    for input in -2..=10 {
+        for (program, holds) in comparisons.iter() {
+            assert_eq!(vec![holds(input) as isize], run(program, &[input]).0);
+        }
+        for program in jumps.iter() {
+            assert_eq!(vec![(input != 0) as isize], run(program, &[input]).0);
+        }
+        let output = 1000 + (input - 8).signum();
+        assert_eq!(vec![output], run(&larger, &[input]).0);
+    }
+}
+
+#[test]
+fn day9_examples() {
+    let quine = [
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ];
+    assert_eq!(quine.to_vec(), run(&quine, &[]).0);
+
+    let product = run(&[1102, 34_915_192, 34_915_192, 7, 4, 7, 99, 0], &[]).0;
+    assert_eq!(16, product[0].to_string().len());
+
+    let literal = 1_125_899_906_842_624;
+    assert_eq!(vec![literal], run(&[104, literal, 99], &[]).0);
+}
@@ -0,0 +1,176 @@
+//! Differential tests checking that `FastMachine` behaves exactly as the interpreter.
+mod common;
+
+use common::Lcg;
+use intcode_computer::fast::FastMachine;
+use intcode_computer::{ExecutionState, IntcodeError, Machine};
+
+const STEP_LIMIT: usize = 10_000;
+
+// Runs `program` on both engines in lockstep, comparing the full machine state after every
+// instruction. Returns the outputs produced.
+fn run_both(program: Vec<isize>, inputs: &[isize], limit: usize) -> Vec<isize> {
+    let mut machine = Machine::new(program.clone()).with_memory_limit(limit);
+    let mut fast = FastMachine::new(Machine::new(program).with_memory_limit(limit));
+    let (mut slow_inputs, mut fast_inputs) = (inputs.iter().copied(), inputs.iter().copied());
+    let (mut slow_outputs, mut fast_outputs) = (Vec::new(), Vec::new());
+
+    for _ in 0..STEP_LIMIT {
+        let slow_result = machine.step(&mut || slow_inputs.next(), &mut |out| {
+            slow_outputs.push(out)
+        });
+        let fast_result = fast.step(&mut || fast_inputs.next(), &mut |out| {
+            fast_outputs.push(out)
+        });
+
+        let fast_machine = fast.machine();
+        assert_eq!(slow_result, fast_result);
+        assert_eq!(slow_outputs, fast_outputs);
+        assert_eq!(machine.exec_state(), fast_machine.exec_state());
+        assert_eq!(machine.inst_pointer(), fast_machine.inst_pointer());
+        assert_eq!(machine.relative_base(), fast_machine.relative_base());
+        assert_eq!(machine.memory(), fast_machine.memory());
+
+        if machine.exec_state() != &ExecutionState::Running {
+            break;
+        }
+    }
+
+    fast_outputs
+}
+
+#[test]
+fn comparisons() {
+    let programs = [
+        vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8],
+        vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8],
+        vec![3, 3, 1108, -1, 8, 3, 4, 3, 99],
+        vec![3, 3, 1107, -1, 8, 3, 4, 3, 99],
+        vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9],
+        vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1],
+    ];
+
+    for program in programs.iter() {
+        for input in -2..=10 {
+            run_both(program.clone(), &[input], 1 << 20);
+        }
+    }
+}
+
+#[test]
+fn larger_comparison() {
+    let program = vec![
+        3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+        1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20,
+        1105, 1, 46, 98, 99,
+    ];
+
+    assert_eq!(vec![999], run_both(program.clone(), &[7], 1 << 20));
+    assert_eq!(vec![1000], run_both(program.clone(), &[8], 1 << 20));
+    assert_eq!(vec![1001], run_both(program, &[9], 1 << 20));
+}
+
+#[test]
+fn quine() {
+    let program = vec![
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ];
+
+    assert_eq!(program, run_both(program.clone(), &[], 1 << 20));
+}
+
+#[test]
+fn self_modification() {
+    // Rewrites its own add into a multiply, its own operands and the target of its own jump
+    let programs = [
+        vec![
+            1, 16, 16, 16, 1001, 0, 1, 0, 1008, 0, 3, 17, 1006, 17, 0, 99, 3, 0,
+        ],
+        vec![
+            1101, 10, 20, 30, 1001, 1, 5, 1, 1007, 1, 40, 31, 1005, 31, 0, 99,
+        ],
+        vec![1101, 0, 7, 6, 1105, 1, 0, 99, 4, 0, 99],
+        vec![3, 1, 99],
+    ];
+
+    for program in programs.iter() {
+        run_both(program.clone(), &[1, 2, 3], 1 << 20);
+    }
+}
+
+#[test]
+fn faults() {
+    let programs = [
+        // Unknown opcode
+        vec![1, 0, 0, 0, 42],
+        // Immediate mode for a write
+        vec![11101, 1, 1, 0, 99],
+        // Unknown parameter mode, which the interpreter reads as position mode
+        vec![301, 0, 0, 0, 99],
+        // Halt with stray parameter modes
+        vec![1199],
+        // Negative addresses
+        vec![1, -1, 0, 0, 99],
+        vec![109, -5, 2201, 0, 0, 0, 99],
+        // Jump to a negative address
+        vec![1105, 1, -7],
+        // Overflow
+        vec![1102, 1 << 62, 4, 0, 99],
+        vec![109, isize::MAX, 109, 1, 99],
+        // Running off the memory limit
+        vec![1101, 1, 1, 100, 99],
+        vec![1105, 1, 14],
+        // Input exhausted
+        vec![3, 0, 3, 0, 99],
+    ];
+
+    for program in programs.iter() {
+        run_both(program.clone(), &[5], 16);
+    }
+}
+
+#[test]
+fn restore_discards_cached_instructions() {
+    let mut fast = FastMachine::new(Machine::new(vec![1, 0, 0, 0, 99]));
+    let snapshot = fast.machine().snapshot();
+    fast.execute(&mut || None, &mut |_out| {}).unwrap();
+    assert_eq!(2, fast.result());
+
+    fast.restore(&snapshot);
+    fast.machine_mut().memory_mut().set(0, 2).unwrap();
+    fast.execute(&mut || None, &mut |_out| {}).unwrap();
+    assert_eq!(4, fast.result());
+    assert_eq!(
+        Err(IntcodeError::NotRunning),
+        fast.execute(&mut || None, &mut |_out| {})
+    );
+}
+
+#[test]
+fn random_programs() {
+    let opcodes = [1, 2, 3, 4, 5, 6, 7, 8, 9, 99];
+    let mut rng = Lcg(2019);
+
+    for _ in 0..2000 {
+        let length = 8 + rng.next(40);
+        let program = (0..length)
+            .map(|_| match rng.next(4) {
+                // Instructions with random parameter modes, mostly valid
+                0 | 1 => {
+                    let modes = (0..3).fold(0, |modes, _| modes * 10 + rng.next(3) as isize);
+                    let modes = if rng.next(20) == 0 { 3 } else { modes };
+                    modes * 100 + opcodes[rng.next(opcodes.len())]
+                }
+                // Addresses within the program, so instructions overwrite each other
+                2 => rng.next(length) as isize,
+                // Small values, including negative ones
+                _ => rng.next(21) as isize - 10,
+            })
+            .collect::<Vec<_>>();
+        let inputs = (0..8)
+            .map(|_| rng.next(length) as isize)
+            .collect::<Vec<_>>();
+
+        run_both(program, &inputs, 64);
+    }
+}
@@ -0,0 +1,234 @@
+//! Operations registered with an `InstructionSet`.
+use std::sync::{Arc, Mutex};
+
+use intcode_computer::fast::FastMachine;
+use intcode_computer::isa::{InstructionSet, Operands, Operation, RegisterError};
+use intcode_computer::trace::{Profile, TraceLog};
+use intcode_computer::{Event, ExecutionState, IntcodeError, Machine};
+
+// Opcode 10; records the value of its parameter without changing the machine
+struct Debug(Arc<Mutex<Vec<isize>>>);
+
+impl Operation for Debug {
+    fn name(&self) -> &str {
+        "DBG"
+    }
+
+    fn writes(&self) -> &[bool] {
+        &[false]
+    }
+
+    fn execute(&self, operands: &mut Operands) -> Result<(), IntcodeError> {
+        self.0.lock().unwrap().push(*operands.get(0));
+        Ok(())
+    }
+}
+
+// Opcode 11; swaps the values of its two parameters
+struct Swap;
+
+impl Operation for Swap {
+    fn name(&self) -> &str {
+        "SWP"
+    }
+
+    fn writes(&self) -> &[bool] {
+        &[true, true]
+    }
+
+    fn execute(&self, operands: &mut Operands) -> Result<(), IntcodeError> {
+        let (first, second) = (*operands.get(0), *operands.get(1));
+        operands.set(0, second);
+        operands.set(1, first);
+        Ok(())
+    }
+}
+
+// Opcode 12; outputs its parameter then jumps to the second, or halts if the second is zero
+struct OutputThenJump;
+
+impl Operation for OutputThenJump {
+    fn name(&self) -> &str {
+        "OJ"
+    }
+
+    fn writes(&self) -> &[bool] {
+        &[false, false]
+    }
+
+    fn execute(&self, operands: &mut Operands) -> Result<(), IntcodeError> {
+        match *operands.get(1) {
+            0 => operands.halt(),
+            target => {
+                operands.output(*operands.get(0));
+                operands.jump(target);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn instructions(log: &Arc<Mutex<Vec<isize>>>) -> InstructionSet {
+    let mut instructions = InstructionSet::standard();
+    instructions.register(10, Debug(log.clone())).unwrap();
+    instructions.register(11, Swap).unwrap();
+    instructions.register(12, OutputThenJump).unwrap();
+    instructions
+}
+
+#[test]
+fn register() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut instructions = instructions(&log);
+    assert_eq!(
+        Err(RegisterError::Standard(1)),
+        instructions.register(1, Swap)
+    );
+    assert_eq!(
+        Err(RegisterError::Standard(99)),
+        instructions.register(99, Swap)
+    );
+    assert_eq!(
+        Err(RegisterError::OutOfRange(100)),
+        instructions.register(100, Swap)
+    );
+    assert_eq!(
+        Err(RegisterError::OutOfRange(-1)),
+        instructions.register(-1, Swap)
+    );
+    assert_eq!(
+        Err(RegisterError::Taken(11)),
+        instructions.register(11, Swap)
+    );
+
+    assert_eq!(vec![10, 11, 12], instructions.opcodes());
+    assert_eq!(Some("SWP"), instructions.name(11));
+    assert_eq!(Some(&[true, true][..]), instructions.writes(11));
+    assert_eq!(Some(&[true][..]), instructions.writes(3));
+    assert_eq!(None, instructions.writes(13));
+}
+
+#[test]
+fn standard_set_rejects_extensions() {
+    let mut machine = Machine::new(vec![11, 5, 6, 99, 0, 1, 2]);
+    assert_eq!(
+        Err(IntcodeError::UnknownOpcode {
+            opcode: 11,
+            address: 0
+        }),
+        machine.execute(&mut || None, &mut |_out| {})
+    );
+}
+
+#[test]
+fn operations_execute() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    // Swap two cells, debug print both in position and relative mode, output one then halt
+    let program = vec![
+        109, 14, 11, 14, 15, 10, 14, 210, 1, 1212, 1, 0, 99, 0, 30, 40,
+    ];
+    let mut machine = Machine::new(program).with_instruction_set(instructions(&log));
+    let mut outputs = Vec::new();
+    machine
+        .execute(&mut || None, &mut |out| outputs.push(out))
+        .unwrap();
+
+    assert_eq!(vec![40, 30], *log.lock().unwrap());
+    assert_eq!(&[40, 30], &machine.memory().image()[14..]);
+    assert_eq!(&ExecutionState::Halted, machine.exec_state());
+    assert!(outputs.is_empty());
+}
+
+#[test]
+fn operations_output_and_jump() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    // Output 7 and jump over the halt to an operation that halts without output
+    let program = vec![1112, 7, 4, 99, 1112, 8, 0];
+    let mut machine = Machine::new(program).with_instruction_set(instructions(&log));
+    assert_eq!(Ok(Event::Output(7)), machine.run_until_event());
+    assert_eq!(4, machine.inst_pointer());
+    assert_eq!(Ok(Event::Halted), machine.run_until_event());
+
+    // Jumps to negative addresses fault as they do for the standard instructions
+    let mut machine = Machine::new(vec![1112, 7, -4]).with_instruction_set(instructions(&log));
+    assert_eq!(
+        Err(IntcodeError::NegativeAddress {
+            target: -4,
+            address: 0
+        }),
+        machine.execute(&mut || None, &mut |_out| {})
+    );
+}
+
+#[test]
+fn written_parameters_cannot_be_immediate() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut machine =
+        Machine::new(vec![1011, 4, 5, 99, 0, 0]).with_instruction_set(instructions(&log));
+    assert_eq!(
+        Err(IntcodeError::BadParameterMode {
+            mode: 1,
+            address: 0
+        }),
+        machine.execute(&mut || None, &mut |_out| {})
+    );
+    assert_eq!(&[0, 0], &machine.memory().image()[4..]);
+}
+
+#[test]
+fn operations_are_traced() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let trace = Arc::new(Mutex::new(TraceLog::default()));
+    let mut machine =
+        Machine::new(vec![11, 4, 5, 99, 1, 2]).with_instruction_set(instructions(&log));
+    machine.set_trace_sink(trace.clone());
+    machine.record_self_modification();
+    machine.execute(&mut || None, &mut |_out| {}).unwrap();
+
+    let entries = trace.lock().unwrap().entries().to_vec();
+    assert_eq!(2, entries.len());
+    assert_eq!(11, entries[0].opcode);
+    assert_eq!(vec![4, 5], entries[0].operands);
+    assert_eq!("SWP", entries[0].name);
+    assert_eq!("     0: SWP 4, 5 -> [5] = 1", entries[0].to_string());
+
+    // The instruction is three words long, so the writes after it are not to code
+    assert!(machine.self_modification().unwrap().writes().is_empty());
+
+    let profile = Arc::new(Mutex::new(Profile::default()));
+    let mut machine =
+        Machine::new(vec![11, 4, 5, 99, 1, 2]).with_instruction_set(instructions(&log));
+    machine.set_trace_sink(profile.clone());
+    machine.execute(&mut || None, &mut |_out| {}).unwrap();
+    assert!(profile.lock().unwrap().to_string().contains("  SWP    1\n"));
+}
+
+#[test]
+fn fast_machine_runs_operations() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    // Counts down from 3, printing each value with the debug operation
+    let program = vec![10, 11, 1001, 11, -1, 11, 1005, 11, 0, 99, 0, 3];
+    let mut fast = FastMachine::new(Machine::new(program).with_instruction_set(instructions(&log)));
+    fast.execute(&mut || None, &mut |_out| {}).unwrap();
+    assert_eq!(vec![3, 2, 1], *log.lock().unwrap());
+}
+
+#[test]
+fn fast_machine_sees_operation_writes() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    // Swaps an add into the place of a multiply it has already executed, then runs it
+    let program = vec![
+        1102, 3, 5, 16, 11, 0, 17, 1105, 1, 0, 99, 0, 0, 0, 0, 0, 0, 1101,
+    ];
+    let mut fast =
+        FastMachine::new(Machine::new(program.clone()).with_instruction_set(instructions(&log)));
+    let mut machine = Machine::new(program).with_instruction_set(instructions(&log));
+    for _ in 0..4 {
+        let fast_result = fast.step(&mut || None, &mut |_out| {});
+        let result = machine.step(&mut || None, &mut |_out| {});
+        assert_eq!(result, fast_result);
+        assert_eq!(machine.memory(), fast.machine().memory());
+        assert_eq!(machine.inst_pointer(), fast.machine().inst_pointer());
+    }
+    assert_eq!(8, fast.machine().memory().get(16).unwrap());
+}
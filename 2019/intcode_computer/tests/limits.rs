@@ -0,0 +1,180 @@
+//! Step budgets, deadlines and loop detection.
+use std::time::{Duration, Instant};
+
+use intcode_computer::fast::FastMachine;
+use intcode_computer::snapshot::Snapshot;
+use intcode_computer::{Event, ExecutionState, IntcodeError, Machine};
+
+const QUINE: [isize; 16] = [
+    109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+];
+
+// Reads a value then jumps to itself forever
+const STUCK: [isize; 5] = [3, 5, 1105, 1, 2];
+
+// Counts upwards forever, so never repeats a state
+const COUNTER: [isize; 8] = [1001, 7, 1, 7, 1105, 1, 0, 0];
+
+#[test]
+fn budget_resumes_where_it_stopped() {
+    for budget in 1..10 {
+        let mut machine = Machine::new(QUINE.to_vec()).with_step_budget(budget);
+        let mut outputs = Vec::new();
+        let mut stops = 0;
+        while let Err(why) = machine.execute(&mut || None, &mut |out| outputs.push(out)) {
+            assert_eq!(IntcodeError::BudgetExhausted, why);
+            assert_eq!(&ExecutionState::BudgetExhausted, machine.exec_state());
+            assert_eq!(Some(0), machine.step_budget());
+            machine.set_step_budget(Some(budget));
+            stops += 1;
+        }
+
+        assert_eq!(QUINE.to_vec(), outputs);
+        assert_eq!(&ExecutionState::Halted, machine.exec_state());
+        assert!(stops > 0);
+    }
+}
+
+#[test]
+fn exhausted_machine_does_not_step() {
+    let mut machine = Machine::new(QUINE.to_vec()).with_step_budget(0);
+    assert_eq!(
+        Err(IntcodeError::BudgetExhausted),
+        machine.step(&mut || None, &mut |_out| {})
+    );
+    assert_eq!(0, machine.inst_pointer());
+    assert_eq!(
+        Err(IntcodeError::NotRunning),
+        machine.step(&mut || None, &mut |_out| {})
+    );
+
+    // Removing the budget also resumes the machine
+    machine.set_step_budget(None);
+    assert_eq!(Ok(()), machine.step(&mut || None, &mut |_out| {}));
+    assert_eq!(1, machine.relative_base());
+}
+
+#[test]
+fn waiting_for_input_is_not_a_step() {
+    let mut machine = Machine::new(vec![3, 0, 4, 0, 99]).with_step_budget(2);
+    assert_eq!(Ok(Event::NeedsInput), machine.run_until_event());
+    assert_eq!(Ok(Event::NeedsInput), machine.run_until_event());
+    assert_eq!(Some(2), machine.step_budget());
+
+    machine.push_input(7);
+    assert_eq!(Ok(Event::Output(7)), machine.run_until_event());
+    assert_eq!(
+        Err(IntcodeError::BudgetExhausted),
+        machine.run_until_event()
+    );
+    machine.set_step_budget(Some(1));
+    assert_eq!(Ok(Event::Halted), machine.run_until_event());
+}
+
+#[test]
+fn fast_machine_respects_budget() {
+    let mut fast = FastMachine::new(Machine::new(COUNTER.to_vec()).with_step_budget(1000));
+    assert_eq!(
+        Err(IntcodeError::BudgetExhausted),
+        fast.execute(&mut || None, &mut |_out| {})
+    );
+    // Each pass of the loop is two instructions
+    assert_eq!(500, fast.machine().memory().get(7).unwrap());
+
+    fast.machine_mut().set_step_budget(Some(10));
+    assert_eq!(
+        Err(IntcodeError::BudgetExhausted),
+        fast.execute(&mut || None, &mut |_out| {})
+    );
+    assert_eq!(505, fast.machine().memory().get(7).unwrap());
+}
+
+#[test]
+fn exhausted_state_survives_snapshot() {
+    let mut machine = Machine::new(QUINE.to_vec()).with_step_budget(5);
+    let mut outputs = Vec::new();
+    let _ = machine.execute(&mut || None, &mut |out| outputs.push(out));
+
+    let saved = machine.snapshot().to_string();
+    assert!(saved.contains("state budget-exhausted\n"));
+    let mut restored = Machine::from_snapshot(&saved.parse::<Snapshot>().unwrap());
+    assert_eq!(&ExecutionState::BudgetExhausted, restored.exec_state());
+
+    restored.set_step_budget(None);
+    restored
+        .execute(&mut || None, &mut |out| outputs.push(out))
+        .unwrap();
+    assert_eq!(QUINE.to_vec(), outputs);
+}
+
+#[test]
+fn deadline_stops_a_runaway_program() {
+    let start = Instant::now();
+    let mut machine =
+        Machine::new(COUNTER.to_vec()).with_deadline(start + Duration::from_millis(50));
+    assert_eq!(
+        Err(IntcodeError::BudgetExhausted),
+        machine.execute(&mut || None, &mut |_out| {})
+    );
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    // A later deadline lets it carry on
+    let count = machine.memory().get(7).unwrap();
+    machine.set_deadline(Some(Instant::now() + Duration::from_millis(10)));
+    let _ = machine.execute(&mut || None, &mut |_out| {});
+    assert!(machine.memory().get(7).unwrap() > count);
+}
+
+#[test]
+fn detects_loops() {
+    let mut machine = Machine::new(STUCK.to_vec()).with_loop_detection();
+    let error = machine
+        .execute(&mut || Some(1), &mut |_out| {})
+        .unwrap_err();
+    assert_eq!(
+        IntcodeError::InfiniteLoop {
+            address: 2,
+            period: 1
+        },
+        error
+    );
+    assert_eq!(&ExecutionState::Faulted(error), machine.exec_state());
+
+    // The state the loop was found in can be saved
+    let saved = machine.snapshot().to_string();
+    assert!(saved.contains("state faulted infinite-loop 2 1\n"));
+    assert_eq!(
+        machine.exec_state(),
+        Machine::from_snapshot(&saved.parse::<Snapshot>().unwrap()).exec_state()
+    );
+}
+
+#[test]
+fn progress_is_not_a_loop() {
+    let mut machine = Machine::new(COUNTER.to_vec())
+        .with_loop_detection()
+        .with_step_budget(100_000);
+    assert_eq!(
+        Err(IntcodeError::BudgetExhausted),
+        machine.execute(&mut || None, &mut |_out| {})
+    );
+
+    let mut machine = Machine::new(QUINE.to_vec()).with_loop_detection();
+    let mut outputs = Vec::new();
+    machine
+        .execute(&mut || None, &mut |out| outputs.push(out))
+        .unwrap();
+    assert_eq!(QUINE.to_vec(), outputs);
+}
+
+#[test]
+fn input_breaks_a_loop() {
+    // Echoes its input forever, returning to the same state whenever the input repeats
+    let mut machine = Machine::new(vec![3, 7, 4, 7, 1105, 1, 0, 0]).with_loop_detection();
+    for _ in 0..100 {
+        assert_eq!(Ok(Event::NeedsInput), machine.run_until_event());
+        machine.push_input(3);
+        assert_eq!(Ok(Event::Output(3)), machine.run_until_event());
+    }
+}
@@ -0,0 +1,75 @@
+//! Checks loading programs in both formats.
+use intcode_computer::loader::{pack, parse, LoadError};
+use intcode_computer::Machine;
+
+const PROGRAM: [isize; 8] = [109, -1, 204, 1, 99, isize::MIN, isize::MAX, 0];
+
+#[test]
+fn detects_format() {
+    let text = PROGRAM
+        .iter()
+        .map(|word| word.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut image = Vec::new();
+    pack(&PROGRAM, &mut image).unwrap();
+
+    for bytes in [text.into_bytes(), image].iter() {
+        let machine = Machine::from_reader(&bytes[..]).unwrap();
+        assert_eq!(&PROGRAM[..], machine.memory().image());
+    }
+}
+
+#[test]
+fn rejects_malformed_images() {
+    let mut image = Vec::new();
+    pack(&PROGRAM, &mut image).unwrap();
+
+    // Anything shorter than the header is read as text
+    for length in 8..image.len() {
+        match Machine::from_reader(&image[..length]) {
+            Err(LoadError::BadImage(_)) => {}
+            other => panic!("{} bytes loaded as {:?}", length, other.map(|_| ())),
+        }
+    }
+}
+
+#[test]
+fn tolerates_whitespace() {
+    assert_eq!(Vec::<isize>::new(), parse::<isize>(" \n").unwrap());
+    assert_eq!(vec![1, 2], parse::<isize>("\t1 ,\r\n 2,\r\n").unwrap());
+}
+
+#[test]
+fn reports_bad_tokens() {
+    let cases = [
+        ("1,,2", 1, 1, 3, ""),
+        ("1,2,\n,", 2, 2, 1, ""),
+        ("1, 2x", 1, 1, 4, "2x"),
+        ("1,\n\n  -,3", 1, 3, 3, "-"),
+        (
+            "99999999999999999999999",
+            0,
+            1,
+            1,
+            "99999999999999999999999",
+        ),
+    ];
+
+    for &(text, index, line, column, token) in cases.iter() {
+        match parse::<isize>(text) {
+            Err(LoadError::BadToken {
+                index: found_index,
+                line: found_line,
+                column: found_column,
+                token: found_token,
+            }) => assert_eq!(
+                (index, line, column, token),
+                (found_index, found_line, found_column, found_token.as_str()),
+                "{:?}",
+                text
+            ),
+            other => panic!("{:?} parsed as {:?}", text, other),
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Properties that must hold for any program; checked against randomly generated ones.
+mod common;
+
+use std::panic;
+
+use common::Lcg;
+use intcode_computer::instruction::MNEMONICS;
+use intcode_computer::snapshot::Snapshot;
+use intcode_computer::{ExecutionState, IntcodeError, Machine};
+
+const PROGRAMS: usize = 1000;
+const STEP_LIMIT: usize = 500;
+const MEMORY_LIMIT: usize = 256;
+
+// Generates a program of valid instructions whose operands mostly stay within the program,
+// followed by some data
+fn well_formed(rng: &mut Lcg) -> Vec<isize> {
+    let count = 1 + rng.next(12);
+    let mut program = Vec::new();
+    let mut starts = Vec::new();
+
+    for _ in 0..count {
+        let mnemonic = MNEMONICS[rng.next(MNEMONICS.len())];
+        starts.push(program.len());
+        program.push(0);
+        let mut modes = 0;
+        for (index, &writes) in mnemonic.writes().iter().enumerate() {
+            let mode = if writes {
+                [0, 2][rng.next(2)]
+            } else {
+                rng.next(3) as isize
+            };
+            modes += mode * 10isize.pow(index as u32);
+            program.push(match mode {
+                1 => rng.next(21) as isize - 10,
+                2 => rng.next(16) as isize - 4,
+                _ => rng.next(64) as isize,
+            });
+        }
+        let opcode = program.len() - mnemonic.arity() - 1;
+        program[opcode] = modes * 100 + mnemonic.opcode();
+    }
+    program.push(99);
+
+    // Point some immediate jump targets at the start of an instruction
+    for &start in &starts {
+        let is_jump = program[start] % 100 == 5 || program[start] % 100 == 6;
+        if is_jump && program[start] / 1000 % 10 == 1 && rng.next(2) == 0 {
+            program[start + 2] = starts[rng.next(starts.len())] as isize;
+        }
+    }
+
+    let data = rng.next(16);
+    program.extend((0..data).map(|_| rng.next(41) as isize - 20));
+    program
+}
+
+// Generates any words at all, including extreme values
+fn arbitrary(rng: &mut Lcg) -> Vec<isize> {
+    let length = 1 + rng.next(32);
+    (0..length)
+        .map(|_| match rng.next(8) {
+            0 => isize::MAX - rng.next(3) as isize,
+            1 => isize::MIN + rng.next(3) as isize,
+            2 => rng.next(100_000) as isize * 100 + [1, 2, 3, 4, 5, 6, 7, 8, 9, 99][rng.next(10)],
+            3 => -(rng.next(100_000) as isize),
+            4 => rng.next(1 << 20) as isize,
+            _ => rng.next(300) as isize - 50,
+        })
+        .collect()
+}
+
+fn inputs(rng: &mut Lcg) -> Vec<isize> {
+    (0..rng.next(6))
+        .map(|_| rng.next(41) as isize - 20)
+        .collect()
+}
+
+// Steps the machine until it stops or reaches the step limit, checking that every fault is
+// recorded in its state. Returns the outputs.
+fn run(machine: &mut Machine, inputs: &mut dyn Iterator<Item = isize>, steps: usize) -> Vec<isize> {
+    let mut outputs = Vec::new();
+    for _ in 0..steps {
+        match machine.step(&mut || inputs.next(), &mut |out| outputs.push(out)) {
+            Ok(()) => {}
+            Err(why) => {
+                assert_eq!(&ExecutionState::Faulted(why), machine.exec_state());
+                break;
+            }
+        }
+        if machine.exec_state() != &ExecutionState::Running {
+            break;
+        }
+    }
+    outputs
+}
+
+#[test]
+fn never_panics() {
+    let mut rng = Lcg(5);
+
+    for index in 0..2 * PROGRAMS {
+        let program = if index % 2 == 0 {
+            well_formed(&mut rng)
+        } else {
+            arbitrary(&mut rng)
+        };
+        let inputs = inputs(&mut rng);
+
+        let result = panic::catch_unwind(|| {
+            let mut machine = Machine::new(program.clone()).with_memory_limit(MEMORY_LIMIT);
+            run(&mut machine, &mut inputs.iter().copied(), STEP_LIMIT);
+            machine
+        });
+        let mut machine = match result {
+            Ok(machine) => machine,
+            Err(_) => panic!("{:?} with inputs {:?} panicked", program, inputs),
+        };
+
+        // Once stopped, the machine refuses to go on
+        if machine.exec_state() != &ExecutionState::Running {
+            assert_eq!(
+                Err(IntcodeError::NotRunning),
+                machine.step(&mut || Some(0), &mut |_out| {})
+            );
+        }
+    }
+}
+
+#[test]
+fn snapshot_then_resume() {
+    let mut rng = Lcg(9);
+
+    for _ in 0..PROGRAMS {
+        let program = well_formed(&mut rng);
+        let inputs = inputs(&mut rng);
+        let pause = rng.next(STEP_LIMIT / 10);
+
+        let mut uninterrupted = Machine::new(program.clone()).with_memory_limit(MEMORY_LIMIT);
+        let expected = run(&mut uninterrupted, &mut inputs.iter().copied(), STEP_LIMIT);
+
+        // Pause, save the state as text, then carry on in a new machine
+        let mut inputs = inputs.iter().copied();
+        let mut first = Machine::new(program.clone()).with_memory_limit(MEMORY_LIMIT);
+        let mut outputs = run(&mut first, &mut inputs, pause);
+        let saved = first.snapshot().to_string();
+        let mut second = Machine::from_snapshot(&saved.parse::<Snapshot>().unwrap());
+        if second.exec_state() == &ExecutionState::Running {
+            outputs.extend(run(&mut second, &mut inputs, STEP_LIMIT - pause));
+        }
+
+        assert_eq!(expected, outputs, "{:?}", program);
+        assert_eq!(uninterrupted.snapshot(), second.snapshot(), "{:?}", program);
+    }
+}
+
+#[test]
+fn budget_then_resume() {
+    let mut rng = Lcg(13);
+
+    for _ in 0..PROGRAMS {
+        let program = well_formed(&mut rng);
+        let inputs = inputs(&mut rng);
+
+        let mut uninterrupted = Machine::new(program.clone()).with_memory_limit(MEMORY_LIMIT);
+        let expected = run(&mut uninterrupted, &mut inputs.iter().copied(), STEP_LIMIT);
+
+        // Run the same number of steps in small pieces, refilling the budget each time
+        let mut inputs = inputs.iter().copied();
+        let mut machine = Machine::new(program.clone()).with_memory_limit(MEMORY_LIMIT);
+        let mut outputs = Vec::new();
+        let mut remaining = STEP_LIMIT;
+        while remaining > 0 {
+            let budget = (1 + rng.next(20)).min(remaining);
+            machine.set_step_budget(Some(budget));
+            let result = machine.execute(&mut || inputs.next(), &mut |out| outputs.push(out));
+            remaining -= budget - machine.step_budget().unwrap();
+            if result != Err(IntcodeError::BudgetExhausted) {
+                break;
+            }
+            assert_eq!(&ExecutionState::BudgetExhausted, machine.exec_state());
+        }
+        if remaining == 0 {
+            machine.set_step_budget(None);
+        }
+
+        assert_eq!(expected, outputs, "{:?}", program);
+        assert_eq!(
+            uninterrupted.snapshot(),
+            machine.snapshot(),
+            "{:?}",
+            program
+        );
+    }
+}
+
+#[test]
+fn loops_found_are_infinite() {
+    let mut rng = Lcg(17);
+    let mut found = 0;
+
+    for _ in 0..PROGRAMS {
+        let program = well_formed(&mut rng);
+
+        let mut machine = Machine::new(program.clone())
+            .with_memory_limit(MEMORY_LIMIT)
+            .with_loop_detection()
+            .with_step_budget(STEP_LIMIT);
+        let (address, period) = match machine.execute(&mut || None, &mut |_out| {}) {
+            Err(IntcodeError::InfiniteLoop { address, period }) => (address, period),
+            _ => continue,
+        };
+        found += 1;
+
+        // Without detection the machine reaches the same state again and again
+        let steps = STEP_LIMIT - machine.step_budget().unwrap();
+        let mut plain = Machine::new(program.clone()).with_memory_limit(MEMORY_LIMIT);
+        run(&mut plain, &mut std::iter::empty(), steps);
+        for _ in 0..3 {
+            let before = plain.snapshot();
+            assert_eq!(address, plain.inst_pointer(), "{:?}", program);
+            run(&mut plain, &mut std::iter::empty(), period);
+            assert_eq!(before, plain.snapshot(), "{:?}", program);
+        }
+    }
+
+    assert!(found > 0);
+}
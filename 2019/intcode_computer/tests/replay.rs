@@ -0,0 +1,145 @@
+//! Checks that recorded sessions reproduce their runs, and notice when a run differs.
+use intcode_computer::replay::{Record, ReplayError, Session};
+use intcode_computer::{ExecutionState, IntcodeError, Machine};
+
+// Outputs 999 if the input is below 8, 1000 if it is 8 and 1001 if it is above 8
+fn larger_comparison() -> Vec<isize> {
+    vec![
+        3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+        1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20,
+        1105, 1, 46, 98, 99,
+    ]
+}
+
+// Sums inputs until it reads a 0, outputting the running total after each
+fn running_total() -> Vec<isize> {
+    vec![
+        3, 15, 1006, 15, 14, 1, 15, 16, 16, 4, 16, 1105, 1, 0, 99, 0, 0,
+    ]
+}
+
+fn record(program: Vec<isize>, inputs: &[isize]) -> (Session, Result<(), IntcodeError>) {
+    let mut inputs = inputs.iter().copied();
+    Machine::new(program).execute_recorded(&mut || inputs.next(), &mut |_out| {})
+}
+
+#[test]
+fn round_trip() {
+    for &input in &[7, 8, 9] {
+        let (session, result) = record(larger_comparison(), &[input]);
+        assert_eq!(Ok(()), result);
+        assert_eq!(vec![input], session.inputs());
+
+        let saved = session.to_string();
+        let loaded = saved.parse::<Session>().unwrap();
+        assert_eq!(session, loaded);
+        assert_eq!(saved, loaded.to_string());
+
+        let outputs = Machine::new(larger_comparison()).replay(&loaded).unwrap();
+        assert_eq!(session.outputs(), outputs);
+        assert_eq!(vec![992 + input], outputs);
+    }
+}
+
+#[test]
+fn interactive() {
+    // Each value is given only once, as if typed by someone at the terminal
+    let mut typed = vec![5, 3, -2, 0].into_iter();
+    let mut shown = Vec::new();
+    let mut machine = Machine::new(running_total());
+    let (session, result) =
+        machine.execute_recorded(&mut || typed.next(), &mut |out| shown.push(out));
+    assert_eq!(Ok(()), result);
+    assert_eq!(vec![5, 8, 6], shown);
+    assert_eq!(
+        vec![
+            (0, Record::Input(5)),
+            (3, Record::Output(5)),
+            (5, Record::Input(3)),
+            (8, Record::Output(8)),
+            (10, Record::Input(-2)),
+            (13, Record::Output(6)),
+            (15, Record::Input(0)),
+        ],
+        session.records()
+    );
+
+    // The replayed machine ends in exactly the same state
+    let mut replayed = Machine::new(running_total());
+    assert_eq!(Ok(shown), replayed.replay(&session));
+    assert_eq!(machine.memory(), replayed.memory());
+    assert_eq!(machine.inst_pointer(), replayed.inst_pointer());
+    assert_eq!(&ExecutionState::Halted, replayed.exec_state());
+}
+
+#[test]
+fn faults_are_recorded() {
+    // The session ends when input runs out, as when stdin is closed
+    let (session, result) = record(running_total(), &[4, 4]);
+    assert_eq!(Err(IntcodeError::InputExhausted), result);
+    assert_eq!(
+        &ExecutionState::Faulted(IntcodeError::InputExhausted),
+        session.end()
+    );
+    assert!(session
+        .to_string()
+        .ends_with(" end faulted input-exhausted\n"));
+
+    let mut machine = Machine::new(running_total());
+    assert_eq!(Ok(vec![4, 8]), machine.replay(&session));
+    assert_eq!(
+        &ExecutionState::Faulted(IntcodeError::InputExhausted),
+        machine.exec_state()
+    );
+}
+
+#[test]
+fn divergence() {
+    let (session, _) = record(larger_comparison(), &[8]);
+
+    // The same input to a different program produces a different output
+    let mut program = larger_comparison();
+    program[24] = 250;
+    assert_eq!(
+        Err(ReplayError {
+            step: 4,
+            expected: String::from("4 out 1000"),
+            found: String::from("4 out 2000"),
+        }),
+        Machine::new(program).replay(&session)
+    );
+
+    // A program that wants no input leaves the recorded input unused
+    let error = Machine::new(vec![104, 1000, 99])
+        .replay(&session)
+        .unwrap_err();
+    assert_eq!(0, error.step);
+    assert_eq!("0 in 8", error.expected);
+
+    // A program that runs for longer than the session
+    let (session, _) = record(vec![1101, 1, 1, 0, 99], &[]);
+    let error = Machine::new(vec![1101, 1, 1, 0, 1101, 1, 1, 0, 99])
+        .replay(&session)
+        .unwrap_err();
+    assert_eq!("2 end halted", error.expected);
+    assert_eq!("2 end running", error.found);
+}
+
+#[test]
+fn malformed() {
+    let bad = [
+        "",
+        "intcode-session 2\n0 end halted\n",
+        "intcode-session 1\n0 in 5\n",
+        "intcode-session 1\nx in 5\n1 end halted\n",
+        "intcode-session 1\n0 sideways 5\n1 end halted\n",
+        "intcode-session 1\n0 in five\n1 end halted\n",
+        "intcode-session 1\n0 in 5 6\n1 end halted\n",
+        "intcode-session 1\n3 in 5\n1 end halted\n",
+        "intcode-session 1\n1 end asleep\n",
+        "intcode-session 1\n1 end halted\n2 in 5\n",
+    ];
+    for text in bad.iter() {
+        assert!(text.parse::<Session>().is_err(), "{:?}", text);
+    }
+}
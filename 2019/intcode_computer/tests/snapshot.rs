@@ -0,0 +1,54 @@
+//! Checks malformed snapshots are rejected with a parse error.
+use intcode_computer::snapshot::{Snapshot, SnapshotError};
+use intcode_computer::Machine;
+
+fn snapshot(limit: usize, image: usize) -> String {
+    format!(
+        "intcode-snapshot 1\nstate running\nip 0\nrelative-base 0\ninputs\n\
+         memory-limit {}\nimage {}\n0: 1 0 0 0 99\nend\n",
+        limit, image
+    )
+}
+
+fn parse_error(text: &str) -> Option<(usize, String)> {
+    match text.parse::<Snapshot>() {
+        Err(SnapshotError::Parse { line, reason }) => Some((line, reason)),
+        _ => None,
+    }
+}
+
+#[test]
+fn loads() {
+    let snapshot = snapshot(1 << 30, 5).parse::<Snapshot>().unwrap();
+    let machine = Machine::from_snapshot(&snapshot);
+    assert_eq!(&[1, 0, 0, 0, 99], machine.memory().image());
+}
+
+#[test]
+fn image_beyond_the_limit() {
+    let (line, _) = parse_error(&snapshot(1 << 30, isize::MAX as usize)).unwrap();
+    assert_eq!(7, line);
+    let (line, _) = parse_error(&snapshot(4, 5)).unwrap();
+    assert_eq!(7, line);
+}
+
+#[test]
+fn image_too_large_to_allocate() {
+    let max = isize::MAX as usize;
+    let (line, reason) = parse_error(&snapshot(max, max)).unwrap();
+    assert_eq!(7, line);
+    assert_eq!("the image is too large to allocate", reason);
+}
+
+#[test]
+fn memory_line_past_the_largest_address() {
+    let text = format!(
+        "intcode-snapshot 1\nstate running\nip 0\nrelative-base 0\ninputs\n\
+         memory-limit {}\nimage 0\n{}: 1 2 3\nend\n",
+        isize::MAX,
+        usize::MAX - 1
+    );
+    let (line, reason) = parse_error(&text).unwrap();
+    assert_eq!(8, line);
+    assert_eq!("the memory line runs past the largest address", reason);
+}
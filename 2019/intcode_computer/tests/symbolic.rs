@@ -0,0 +1,69 @@
+//! Checks that symbolic analysis agrees with concrete execution.
+use intcode_computer::symbolic::{analyse, solve_noun_verb, AnalysisError, Linear};
+use intcode_computer::Machine;
+
+// Shaped like a day 2 program; the first instruction uses the noun and verb as addresses, but
+// its result is overwritten before it is used
+fn program() -> Vec<isize> {
+    vec![
+        1, 0, 0, 3, 1, 1, 2, 3, 1, 3, 4, 3, 1, 5, 0, 3, 2, 1, 10, 19, 1, 19, 5, 23, 2, 23, 9, 27,
+        1, 5, 27, 31, 1, 31, 2, 0, 99,
+    ]
+}
+
+fn run(noun: isize, verb: isize) -> isize {
+    let mut machine = Machine::new(program());
+    machine.set_noun_verb(noun, verb).unwrap();
+    machine.execute(&mut || None, &mut |_out| {}).unwrap();
+    machine.result()
+}
+
+#[test]
+fn agrees_with_execution() {
+    let linear = analyse(&Machine::new(program())).unwrap();
+    assert_eq!(
+        Linear {
+            noun: 12,
+            verb: 1,
+            constant: 4
+        },
+        linear
+    );
+
+    for noun in 0..=99 {
+        for verb in (0..=99).step_by(7) {
+            assert_eq!(Some(run(noun, verb)), linear.evaluate(noun, verb));
+        }
+    }
+}
+
+#[test]
+fn solves_directly() {
+    let machine = Machine::new(program());
+    let (noun, verb) = solve_noun_verb(&machine, 1000, 0..=99).unwrap();
+    assert_eq!(1000, run(noun, verb));
+    assert_eq!((83, 0), (noun, verb));
+
+    assert_eq!(None, solve_noun_verb(&machine, 2000, 0..=99));
+}
+
+#[test]
+fn rejects_what_it_can_not_follow() {
+    // Multiplies the noun by the verb
+    assert_eq!(
+        Err(AnalysisError::NotLinear),
+        analyse(&Machine::new(vec![2, 1, 2, 0, 99]))
+    );
+
+    // Reads input
+    assert_eq!(
+        Err(AnalysisError::Unsupported { address: 0 }),
+        analyse(&Machine::new(vec![3, 0, 0, 99]))
+    );
+
+    // Writes through the sum of the noun and verb
+    assert_eq!(
+        Err(AnalysisError::Symbolic { address: 4 }),
+        analyse(&Machine::new(vec![1101, 0, 0, 7, 1101, 1, 1, 0, 99]))
+    );
+}
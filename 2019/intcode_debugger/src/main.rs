@@ -0,0 +1,329 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::error::Error;
+use std::io;
+use std::io::{BufRead, ErrorKind, Write};
+use std::path::Path;
+
+use intcode_computer::instruction::{Instruction, Mnemonic};
+use intcode_computer::{ExecutionState, Machine};
+
+const HELP: &str = "\
+Commands:
+  step [count]              s    execute one or more instructions
+  continue                  c    run until a breakpoint, watchpoint or the end of the program
+  break <address>           b    stop before executing the instruction at an address
+  break op <opcode>         b    stop before executing any instruction with an opcode or mnemonic
+  watch <address>           w    stop after the value at an address changes
+  delete <address>          d    remove the breakpoint and watchpoint at an address
+  delete op <opcode>        d    remove the breakpoint on an opcode or mnemonic
+  info                      i    show the registers, breakpoints and watchpoints
+  x <address> [count]            dump memory
+  set <address> <value>          patch memory
+  list [address] [count]    l    disassemble, starting at the instruction pointer by default
+  patches                        show the writes the program has made to its own instructions
+  help                      h    show this message
+  quit                      q    exit the debugger
+An empty line repeats the previous command.";
+
+// Why execution stopped while running
+enum Stop {
+    Breakpoint,
+    Watchpoint(usize, isize, isize),
+    NotRunning,
+}
+
+struct Debugger {
+    machine: Machine,
+    breakpoints: BTreeSet<usize>,
+    opcode_breakpoints: BTreeSet<isize>,
+    watchpoints: BTreeMap<usize, isize>,
+}
+
+impl Debugger {
+    fn new(machine: Machine) -> Debugger {
+        Debugger {
+            machine,
+            breakpoints: BTreeSet::new(),
+            opcode_breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new(),
+        }
+    }
+
+    fn read(&self, address: usize) -> isize {
+        self.machine.memory().get(address).unwrap_or(0)
+    }
+
+    fn decode(&self, address: usize) -> Option<Instruction> {
+        let words = (address..address.saturating_add(4))
+            .map(|address| self.read(address))
+            .collect::<Vec<_>>();
+        Instruction::decode(&words, 0)
+    }
+
+    fn describe(&self, address: usize) -> String {
+        match self.decode(address) {
+            Some(instruction) => format!("{:>6}: {}", address, instruction),
+            None => format!("{:>6}: DATA {}", address, self.read(address)),
+        }
+    }
+
+    // Executes one instruction, reporting any watched cell that changed
+    fn step(&mut self) -> Option<Stop> {
+        // Asks again until a number is given, so that a typo does not fault the machine
+        let mut input_fn = || loop {
+            print!("input> ");
+            io::stdout().flush().ok()?;
+            let mut line = String::new();
+            match io::stdin().read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => match line.trim().parse() {
+                    Ok(value) => return Some(value),
+                    Err(_) => println!("expected a number but found {:?}", line.trim()),
+                },
+                Err(why) if why.kind() == ErrorKind::InvalidData => {
+                    println!("Couldn't read the input: {}", why)
+                }
+                Err(why) if why.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return None,
+            }
+        };
+        let mut output_fn = |out| println!("= {}", out);
+
+        if let Err(why) = self.machine.step(&mut input_fn, &mut output_fn) {
+            println!("Fault: {}", why);
+            return Some(Stop::NotRunning);
+        }
+
+        for (&address, old) in self.watchpoints.iter_mut() {
+            let new = self.machine.memory().get(address).unwrap_or(0);
+            if new != *old {
+                let stop = Stop::Watchpoint(address, *old, new);
+                *old = new;
+                return Some(stop);
+            }
+        }
+
+        if self.machine.exec_state() != &ExecutionState::Running {
+            return Some(Stop::NotRunning);
+        }
+
+        None
+    }
+
+    fn at_breakpoint(&self) -> bool {
+        let inst_pointer = self.machine.inst_pointer();
+        self.breakpoints.contains(&inst_pointer)
+            || self
+                .opcode_breakpoints
+                .contains(&(self.read(inst_pointer) % 100))
+    }
+
+    fn report(&self, stop: Option<Stop>) {
+        match stop {
+            Some(Stop::Breakpoint) => println!("Breakpoint"),
+            Some(Stop::Watchpoint(address, old, new)) => {
+                println!("Watchpoint {}: {} -> {}", address, old, new)
+            }
+            Some(Stop::NotRunning) => {
+                println!("Machine {}", self.machine.exec_state());
+                return;
+            }
+            None => {}
+        }
+        println!("{}", self.describe(self.machine.inst_pointer()));
+    }
+
+    fn run(&mut self, count: Option<usize>) {
+        if self.machine.exec_state() != &ExecutionState::Running {
+            self.report(Some(Stop::NotRunning));
+            return;
+        }
+
+        let mut executed = 0;
+        let stop = loop {
+            if let Some(stop) = self.step() {
+                break Some(stop);
+            }
+            executed += 1;
+
+            if count.is_some_and(|count| executed >= count) {
+                break None;
+            }
+            if self.at_breakpoint() {
+                break Some(Stop::Breakpoint);
+            }
+        };
+
+        self.report(stop);
+    }
+
+    fn info(&self) {
+        println!("state:         {}", self.machine.exec_state());
+        println!("ip:            {}", self.machine.inst_pointer());
+        println!("relative base: {}", self.machine.relative_base());
+        println!("breakpoints:   {:?}", self.breakpoints);
+        println!(
+            "opcode breaks: {:?}",
+            self.opcode_breakpoints
+                .iter()
+                .map(|&opcode| Mnemonic::from_opcode(opcode)
+                    .map_or(opcode.to_string(), |mnemonic| mnemonic.to_string()))
+                .collect::<Vec<_>>()
+        );
+        println!("watchpoints:   {:?}", self.watchpoints);
+        println!("{}", self.describe(self.machine.inst_pointer()));
+    }
+
+    fn dump(&self, address: usize, count: usize) -> Result<(), String> {
+        let end = address.checked_add(count).ok_or_else(|| {
+            format!(
+                "{} cells from {} runs past the end of memory",
+                count, address
+            )
+        })?;
+        for row in (address..end).step_by(8) {
+            let values = (row..row.saturating_add(8).min(end))
+                .map(|address| format!("{:>8}", self.read(address)))
+                .collect::<String>();
+            println!("{:>6}:{}", row, values);
+        }
+        Ok(())
+    }
+
+    fn list(&self, mut address: usize, count: usize) {
+        for _ in 0..count {
+            let marker = if address == self.machine.inst_pointer() {
+                ">"
+            } else {
+                " "
+            };
+            println!("{}{}", marker, self.describe(address));
+            let size = self
+                .decode(address)
+                .map_or(1, |instruction| instruction.size());
+            address = match address.checked_add(size) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+    }
+
+    // Executes a command, returning false when the debugger should exit
+    fn command(&mut self, words: &[&str]) -> Result<bool, String> {
+        let number = |index: usize| -> Result<Option<isize>, String> {
+            match words.get(index) {
+                Some(word) => word
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| format!("expected a number but found {}", word)),
+                None => Ok(None),
+            }
+        };
+        let address = |index: usize| -> Result<Option<usize>, String> {
+            match number(index)? {
+                Some(value) if value < 0 => Err(format!("{} is not a valid address", value)),
+                value => Ok(value.map(|value| value as usize)),
+            }
+        };
+        let required = |value: Option<usize>| value.ok_or_else(|| String::from("missing address"));
+        let opcode = |index: usize| -> Result<isize, String> {
+            let word = words.get(index).ok_or("missing opcode")?;
+            word.parse()
+                .ok()
+                .or_else(|| Mnemonic::from_name(word).map(Mnemonic::opcode))
+                .ok_or_else(|| format!("unknown opcode {}", word))
+        };
+
+        match words[0] {
+            "step" | "s" => self.run(Some(address(1)?.unwrap_or(1))),
+            "continue" | "c" => self.run(None),
+            "break" | "b" if words.get(1) == Some(&"op") => {
+                self.opcode_breakpoints.insert(opcode(2)?);
+            }
+            "break" | "b" => {
+                self.breakpoints.insert(required(address(1)?)?);
+            }
+            "watch" | "w" => {
+                let address = required(address(1)?)?;
+                self.watchpoints.insert(address, self.read(address));
+            }
+            "delete" | "d" if words.get(1) == Some(&"op") => {
+                self.opcode_breakpoints.remove(&opcode(2)?);
+            }
+            "delete" | "d" => {
+                let address = required(address(1)?)?;
+                self.breakpoints.remove(&address);
+                self.watchpoints.remove(&address);
+            }
+            "info" | "i" => self.info(),
+            "x" => self.dump(required(address(1)?)?, address(2)?.unwrap_or(8))?,
+            "set" => {
+                let address = required(address(1)?)?;
+                let value = number(2)?.ok_or("missing value")?;
+                self.machine
+                    .memory_mut()
+                    .set(address, value)
+                    .map_err(|why| why.to_string())?;
+                if let Some(watched) = self.watchpoints.get_mut(&address) {
+                    *watched = value;
+                }
+            }
+            "list" | "l" => self.list(
+                address(1)?.unwrap_or_else(|| self.machine.inst_pointer()),
+                address(2)?.unwrap_or(10),
+            ),
+            "patches" => match self.machine.self_modification() {
+                Some(log) if !log.writes().is_empty() => print!("{}", log),
+                _ => println!("The program has not modified its own instructions"),
+            },
+            "help" | "h" => println!("{}", HELP),
+            "quit" | "q" => return Ok(false),
+            command => return Err(format!("unknown command {}, try help", command)),
+        }
+
+        Ok(true)
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let arguments = env::args().collect::<Vec<_>>();
+    let path = match arguments.get(1) {
+        Some(path) => Path::new(path),
+        None => return Err("usage: intcode_debugger <PROGRAM>".into()),
+    };
+
+    let mut machine = Machine::from_path(path)?;
+    machine.record_self_modification();
+    let mut debugger = Debugger::new(machine);
+    println!("{}", debugger.describe(0));
+
+    let stdin = io::stdin();
+    let mut previous = String::new();
+    loop {
+        print!("(icdb) ");
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break;
+        }
+        if line.trim().is_empty() {
+            line = previous.clone();
+        }
+
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        if words.is_empty() {
+            continue;
+        }
+
+        match debugger.command(&words) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(why) => println!("{}", why),
+        }
+        previous = line;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use intcode_computer::adapters::{decode_into, encode_lines};
+use intcode_computer::{Event, Machine};
+
+const USAGE: &str = "\
+usage: intcode_terminal <PROGRAM> [options]
+  --script <FILE>        take input from the lines of a file before reading from stdin
+  --transcript <FILE>    write everything shown on the terminal to a file
+  --record <FILE>        write every line of input to a file, for replaying with --script";
+
+#[derive(Default)]
+struct Options {
+    program: String,
+    script: Option<String>,
+    transcript: Option<String>,
+    record: Option<String>,
+}
+
+impl Options {
+    fn parse(arguments: &[String]) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut arguments = arguments.iter();
+        while let Some(argument) = arguments.next() {
+            let target = match argument.as_str() {
+                "--script" => &mut options.script,
+                "--transcript" => &mut options.transcript,
+                "--record" => &mut options.record,
+                _ if options.program.is_empty() && !argument.starts_with("--") => {
+                    options.program = argument.clone();
+                    continue;
+                }
+                _ => return Err(USAGE.to_string()),
+            };
+            *target = Some(arguments.next().ok_or(USAGE)?.clone());
+        }
+
+        if options.program.is_empty() {
+            return Err(USAGE.to_string());
+        }
+        Ok(options)
+    }
+}
+
+struct Terminal {
+    machine: Machine,
+    script: VecDeque<String>,
+    transcript: String,
+    commands: Vec<String>,
+}
+
+impl Terminal {
+    // Shows text on the terminal and adds it to the transcript
+    fn show(&mut self, text: &str) -> io::Result<()> {
+        print!("{}", text);
+        io::stdout().flush()?;
+        self.transcript.push_str(text);
+        Ok(())
+    }
+
+    // Gets the next line of input from the script, or from stdin once the script runs out.
+    // Scripted lines are shown as if they had been typed.
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        let line = match self.script.pop_front() {
+            Some(line) => {
+                self.show(&format!("{}\n", line))?;
+                line
+            }
+            None => {
+                let mut line = String::new();
+                if io::stdin().lock().read_line(&mut line)? == 0 {
+                    return Ok(None);
+                }
+                let line = line.trim_end_matches(&['\r', '\n'][..]).to_string();
+                self.transcript.push_str(&line);
+                self.transcript.push('\n');
+                line
+            }
+        };
+
+        self.commands.push(line.clone());
+        Ok(Some(line))
+    }
+
+    // Runs the program until it halts, faults or runs out of input
+    fn run(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut text = String::new();
+        loop {
+            match self.machine.run_until_event()? {
+                Event::Output(out) => {
+                    decode_into(&mut text, out);
+                    if text.ends_with('\n') {
+                        self.show(&text)?;
+                        text.clear();
+                    }
+                }
+                Event::NeedsInput => {
+                    self.show(&text)?;
+                    text.clear();
+                    match self.next_line()? {
+                        Some(line) => {
+                            for code in encode_lines(Some(line)) {
+                                self.machine.push_input(code);
+                            }
+                        }
+                        None => return Ok(()),
+                    }
+                }
+                Event::Halted => {
+                    self.show(&text)?;
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let arguments = env::args().skip(1).collect::<Vec<_>>();
+    let options = Options::parse(&arguments)?;
+
+    let script = match &options.script {
+        Some(path) => fs::read_to_string(path)?
+            .lines()
+            .map(String::from)
+            .collect(),
+        None => VecDeque::new(),
+    };
+
+    let mut terminal = Terminal {
+        machine: Machine::from_path(Path::new(&options.program))?,
+        script,
+        transcript: String::new(),
+        commands: Vec::new(),
+    };
+    let result = terminal.run();
+
+    // Keep whatever was recorded, even if the program faulted
+    if let Some(path) = &options.transcript {
+        fs::write(path, &terminal.transcript)?;
+    }
+    if let Some(path) = &options.record {
+        let mut commands = terminal.commands.join("\n");
+        if !terminal.commands.is_empty() {
+            commands.push('\n');
+        }
+        fs::write(path, commands)?;
+    }
+
+    result
+}